@@ -0,0 +1,167 @@
+//! Batch processing of many inputs in a single run.
+
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use anyhow::{Result, anyhow, bail};
+use serde_json::{Map, Value, json};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    cli::{Cli, parse_date},
+    formats::{InputFormat, OutputFormat},
+    output::{JSON_SCHEMA_VERSION, json_output, text_output},
+};
+
+/// One input to be processed, along with metadata that overrides `--name`,
+/// `--note`, and `--date` for that input only.
+#[derive(Debug, Default)]
+struct BatchRecord {
+    input: Option<String>,
+    name: Option<String>,
+    note: Option<String>,
+    date: Option<String>,
+}
+
+impl BatchRecord {
+    /// A line containing a JSON object is a record, any other line is an
+    /// input.
+    fn parse(line: &str) -> Result<Self> {
+        if !line.starts_with('{') {
+            let mut record = Self::default();
+            record.input = Some(line.to_string());
+            return Ok(record);
+        }
+        let mut object: Map<String, Value> = serde_json::from_str(line)
+            .map_err(|_| anyhow!("Batch record must be a JSON object."))?;
+        let mut field = |key: &str| -> Result<Option<String>> {
+            match object.remove(key) {
+                None | Some(Value::Null) => Ok(None),
+                Some(Value::String(s)) => Ok(Some(s)),
+                Some(_) => {
+                    bail!("Batch record field \"{}\" must be a string.", key)
+                }
+            }
+        };
+        let record = Self {
+            input: field("input")?,
+            name: field("name")?,
+            note: field("note")?,
+            date: field("date")?,
+        };
+        Ok(record)
+    }
+}
+
+impl Drop for BatchRecord {
+    fn drop(&mut self) { self.input.zeroize(); }
+}
+
+/// Reads the records to be processed, each paired with its record number.
+/// For `--batch`, the record number is the line number in the file.
+fn read_records(state: &Cli) -> Result<Vec<(usize, Result<BatchRecord>)>> {
+    if let Some(count) = state.batch_count {
+        return Ok((1..=count)
+            .map(|n| (n, Ok(BatchRecord::default())))
+            .collect());
+    }
+    let path = state.batch.as_deref().expect("Batch not configured");
+    let contents = if path == "-" {
+        let mut contents = Zeroizing::new(String::new());
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        Zeroizing::new(fs::read_to_string(path)?)
+    };
+    Ok(contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(n, line)| (n, BatchRecord::parse(line)))
+        .collect())
+}
+
+fn process_record(
+    state: &mut Cli,
+    n: usize,
+    mut record: BatchRecord,
+    input_format: &dyn InputFormat,
+    output_formats: &[Box<dyn OutputFormat>],
+) -> Result<Zeroizing<String>> {
+    if record.input.is_none() && input_format.name() != "random" {
+        bail!("Batch record has no input.");
+    }
+    state.input.zeroize();
+    state.input = record.input.take();
+    if let Some(name) = record.name.take() {
+        state.name = Some(name);
+    }
+    if let Some(note) = record.note.take() {
+        state.note = Some(note);
+    }
+    if let Some(date) = &record.date {
+        state.date = Some(parse_date(date).map_err(|e| anyhow!(e))?);
+    }
+    state.seed = None;
+    input_format.process_input(state)?;
+    state.apply_overrides();
+    if state.json {
+        let mut document = json_output(state, output_formats)?;
+        document["record"] = json!(n);
+        Ok(Zeroizing::new(serde_json::to_string(&document)?))
+    } else {
+        text_output(state, output_formats)
+    }
+}
+
+/// Processes every record, printing the output of each as it completes.
+///
+/// A record that fails is reported and skipped. In JSON mode each output is a
+/// single line with the `record` number added, and a failed record is
+/// reported as a line containing its `record` number and an `error` message.
+pub fn run_batch(
+    mut state: Cli,
+    input_format: &dyn InputFormat,
+    output_formats: &[Box<dyn OutputFormat>],
+) -> Result<()> {
+    let records = read_records(&state)?;
+    let (name, note, date) =
+        (state.name.clone(), state.note.clone(), state.date);
+    let mut failures = 0;
+    for (n, record) in records {
+        state.name = name.clone();
+        state.note = note.clone();
+        state.date = date;
+        let result = record.and_then(|record| {
+            process_record(&mut state, n, record, input_format, output_formats)
+        });
+        match result {
+            Ok(output) => {
+                println!("{}", *output);
+                if !state.json && output.contains('\n') {
+                    println!();
+                }
+            }
+            Err(error) => {
+                failures += 1;
+                if state.json {
+                    let line = json!({
+                        "version": JSON_SCHEMA_VERSION,
+                        "record": n,
+                        "error": error.to_string(),
+                    });
+                    println!("{}", line);
+                } else {
+                    eprintln!("Record {}: {}", n, error);
+                }
+            }
+        }
+    }
+    if failures > 0 {
+        bail!("{} batch record(s) failed.", failures);
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+//! The master key fingerprint and first receive address of a seed, so that
+//! recovered mnemonics can be matched against them.
+
+use anyhow::{Result, anyhow, bail};
+use bitcoin::{
+    AddressType, Network, NetworkKind, PublicKey,
+    address::{Address as BitcoinAddress, NetworkUnchecked},
+    bip32::{ChildNumber, Xpriv},
+    secp256k1::{All, Secp256k1},
+};
+
+/// A BIP-32 master key, whose private key is erased when it is dropped.
+pub struct MasterKey(Xpriv);
+
+impl MasterKey {
+    pub fn new(seed: &[u8]) -> Result<Self> {
+        Ok(Self(Xpriv::new_master(NetworkKind::Main, seed)?))
+    }
+
+    pub fn fingerprint(&self, secp: &Secp256k1<All>) -> [u8; 4] {
+        self.0.fingerprint(secp).to_bytes()
+    }
+}
+
+impl Drop for MasterKey {
+    fn drop(&mut self) { self.0.private_key.non_secure_erase(); }
+}
+
+/// A receive address to be matched, along with how it is derived.
+pub struct Address {
+    address: BitcoinAddress,
+    path: [ChildNumber; 5],
+}
+
+impl Address {
+    /// Parses a mainnet or testnet address, checking its checksum, so that a
+    /// mistyped address is reported before searching rather than matching
+    /// nothing.
+    pub fn parse(address: &str) -> Result<Self> {
+        let address = address.trim();
+        let unchecked: BitcoinAddress<NetworkUnchecked> = address
+            .parse()
+            .map_err(|e| anyhow!("Invalid address \"{}\": {}", address, e))?;
+        let testnet = !unchecked.is_valid_for_network(Network::Bitcoin);
+        let network = if testnet {
+            Network::Testnet
+        } else {
+            Network::Bitcoin
+        };
+        let address = unchecked.require_network(network).map_err(|_| {
+            anyhow!("Unsupported network for address \"{}\".", address)
+        })?;
+        // The purpose of the BIP-44, BIP-49, or BIP-84 derivation path.
+        let purpose = match address.address_type() {
+            Some(AddressType::P2pkh) => 44,
+            Some(AddressType::P2sh) => 49,
+            Some(AddressType::P2wpkh) => 84,
+            _ => bail!(
+                "Unsupported address \"{}\": only legacy, nested SegWit, and native SegWit addresses are supported.",
+                address
+            ),
+        };
+        let coin = if testnet { 1 } else { 0 };
+        let path = [
+            ChildNumber::from_hardened_idx(purpose)?,
+            ChildNumber::from_hardened_idx(coin)?,
+            ChildNumber::from_hardened_idx(0)?,
+            ChildNumber::from_normal_idx(0)?,
+            ChildNumber::from_normal_idx(0)?,
+        ];
+        Ok(Self { address, path })
+    }
+
+    /// Whether the first receive address of the master key is this address.
+    pub fn matches(
+        &self,
+        secp: &Secp256k1<All>,
+        master: &MasterKey,
+    ) -> Result<bool> {
+        let mut key = master.0.derive_priv(secp, &self.path)?;
+        let public_key = PublicKey::new(key.private_key.public_key(secp));
+        key.private_key.non_secure_erase();
+        Ok(self.address.is_related_to_pubkey(&public_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bip39::Mnemonic;
+
+    use super::*;
+
+    // The test vectors of BIP-44, BIP-49, and BIP-84.
+    #[test]
+    fn test_derivation() {
+        let secp = Secp256k1::new();
+        let mnemonic = Mnemonic::parse_normalized(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let master = MasterKey::new(&mnemonic.to_seed("")).unwrap();
+        assert_eq!(hex::encode(master.fingerprint(&secp)), "73c5da0a");
+        for address in [
+            "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA",
+            "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf",
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu",
+            "2Mww8dCYPUpKHofjgcXcBCEGmniw9CoaiD2",
+        ] {
+            let address = Address::parse(address).unwrap();
+            assert!(address.matches(&secp, &master).unwrap());
+        }
+        let address =
+            Address::parse("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2").unwrap();
+        assert!(!address.matches(&secp, &master).unwrap());
+
+        // A mistyped address fails its checksum.
+        assert!(
+            Address::parse("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyv")
+                .is_err()
+        );
+        assert!(Address::parse("1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabB").is_err());
+    }
+}
@@ -1,16 +1,28 @@
-use std::io::{self, Read};
+use std::{
+    cell::OnceCell,
+    fs,
+    io::{self, Read},
+    ops::Deref,
+};
 
-use anyhow::Result;
-use bc_components::{SSKRGroupSpec, SSKRSpec};
-use bc_envelope::prelude::*;
+use anyhow::{Result, anyhow, bail};
+use bc_components::{
+    PrivateKeyBase, PrivateKeys, PrivateKeysProvider, PublicKeys,
+    SSKRGroupSpec, SSKRSpec, SigningPrivateKey, SigningPublicKey,
+};
+use bc_envelope::{KNOWN_VALUES, prelude::*};
 use bc_rand::{RandomNumberGenerator, SecureRandomNumberGenerator};
 use clap::Parser;
 use clap_num::number_range;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::{
-    formats::{InputFormatKey, OutputFormatKey, SSKRFormatKey},
+    formats::{
+        ElidedPart, InputFormatKey, OutputFormatKey, PasswordKdf, SSKRFormatKey,
+    },
+    prompt::prompt_secret,
     random::DeterministicRandomNumberGenerator,
-    seed::Seed,
+    seed::{Seed, is_seed_assertion},
     styles,
 };
 
@@ -22,7 +34,211 @@ pub fn parse_group_threshold(s: &str) -> Result<usize, String> {
     number_range(s, 1, 16)
 }
 
-fn parse_date(s: &str) -> Result<Date, String> {
+fn parse_threads(s: &str) -> Result<usize, String> { number_range(s, 1, 1024) }
+
+fn parse_scrypt_log_n(s: &str) -> Result<u8, String> { number_range(s, 10, 24) }
+
+fn parse_scrypt_r(s: &str) -> Result<u32, String> { number_range(s, 1, 32) }
+
+fn parse_scrypt_p(s: &str) -> Result<u32, String> { number_range(s, 1, 16) }
+
+fn parse_fingerprint(s: &str) -> Result<[u8; 4], String> {
+    hex::decode(s)
+        .ok()
+        .and_then(|data| data.try_into().ok())
+        .ok_or_else(|| "must be 4 bytes of hex, e.g. 73c5da0a".to_string())
+}
+
+/// Parses `ur:crypto-prvkeys`, or `ur:crypto-prvkey-base` from which the
+/// private keys are derived.
+fn parse_private_keys(s: &str) -> Result<PrivateKeys, String> {
+    let s = s.trim();
+    if let Ok(keys) = PrivateKeys::from_ur_string(s) {
+        return Ok(keys);
+    }
+    PrivateKeyBase::from_ur_string(s)
+        .map(|base| base.private_keys())
+        .map_err(|_| {
+            "must be a ur:crypto-prvkeys or ur:crypto-prvkey-base".to_string()
+        })
+}
+
+/// A private key given as an option's value: either its UR, or the path of a
+/// file whose first line is the UR, which keeps the key out of shell history
+/// and the process's arguments.
+#[derive(Debug, Clone)]
+pub struct KeyArg<T> {
+    pub key: T,
+    /// Whether the UR itself was given on the command line.
+    pub inline: bool,
+}
+
+impl<T> Deref for KeyArg<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { &self.key }
+}
+
+fn parse_key_arg<T>(
+    s: &str,
+    parse: fn(&str) -> Result<T, String>,
+) -> Result<KeyArg<T>, String> {
+    if s.trim().to_lowercase().starts_with("ur:") {
+        return Ok(KeyArg {
+            key: parse(s)?,
+            inline: true,
+        });
+    }
+    let contents = Zeroizing::new(
+        fs::read_to_string(s)
+            .map_err(|e| format!("can't read the key file: {}", e))?,
+    );
+    let key = parse(contents.lines().next().unwrap_or_default())?;
+    Ok(KeyArg { key, inline: false })
+}
+
+fn parse_private_keys_arg(s: &str) -> Result<KeyArg<PrivateKeys>, String> {
+    parse_key_arg(s, parse_private_keys)
+}
+
+fn parse_signing_private_key_arg(
+    s: &str,
+) -> Result<KeyArg<SigningPrivateKey>, String> {
+    parse_key_arg(s, parse_signing_private_key)
+}
+
+fn parse_public_keys(s: &str) -> Result<PublicKeys, String> {
+    PublicKeys::from_ur_string(s.trim())
+        .map_err(|_| "must be a ur:crypto-pubkeys".to_string())
+}
+
+/// Parses `ur:signing-private-key`, or the signing key of the private keys
+/// accepted by `parse_private_keys`.
+fn parse_signing_private_key(s: &str) -> Result<SigningPrivateKey, String> {
+    if let Ok(key) = SigningPrivateKey::from_ur_string(s.trim()) {
+        return Ok(key);
+    }
+    parse_private_keys(s)
+        .map(|keys| keys.signing_private_key().clone())
+        .map_err(|_| {
+            "must be a ur:signing-private-key, ur:crypto-prvkeys, or ur:crypto-prvkey-base".to_string()
+        })
+}
+
+/// Parses `ur:signing-public-key`, or the signing key of a
+/// `ur:crypto-pubkeys`.
+fn parse_signing_public_key(s: &str) -> Result<SigningPublicKey, String> {
+    if let Ok(key) = SigningPublicKey::from_ur_string(s.trim()) {
+        return Ok(key);
+    }
+    parse_public_keys(s)
+        .map(|keys| keys.signing_public_key().clone())
+        .map_err(|_| {
+            "must be a ur:signing-public-key or ur:crypto-pubkeys".to_string()
+        })
+}
+
+/// One side of an assertion given on the command line.
+#[derive(Debug, Clone)]
+enum AssertionPart {
+    KnownValue(KnownValue),
+    String(String),
+}
+
+impl AssertionPart {
+    /// Parses a known value in single quotes, by name or number (e.g.,
+    /// `'dereferenceVia'`), or otherwise a string.
+    fn parse(s: &str) -> Result<Self, String> {
+        let Some(name) =
+            s.strip_prefix('\'').and_then(|s| s.strip_suffix('\''))
+        else {
+            return Ok(Self::String(s.to_string()));
+        };
+        if let Ok(value) = name.parse::<u64>() {
+            return Ok(Self::KnownValue(KnownValue::new(value)));
+        }
+        KNOWN_VALUES
+            .get()
+            .as_ref()
+            .and_then(|store| store.known_value_named(name).cloned())
+            .map(Self::KnownValue)
+            .ok_or_else(|| format!("'{}' is not a known value", name))
+    }
+
+    fn to_envelope(&self) -> Envelope {
+        match self {
+            Self::KnownValue(value) => Envelope::new(value.clone()),
+            Self::String(string) => Envelope::new(string.as_str()),
+        }
+    }
+}
+
+/// An assertion to add to the seed's envelope.
+#[derive(Debug, Clone)]
+pub struct AssertionArg {
+    predicate: AssertionPart,
+    object: AssertionPart,
+}
+
+impl AssertionArg {
+    pub fn to_envelope(&self) -> Envelope {
+        Envelope::new_assertion(
+            self.predicate.to_envelope(),
+            self.object.to_envelope(),
+        )
+    }
+}
+
+/// Parses an assertion given as `PREDICATE=OBJECT`.
+fn parse_assertion(s: &str) -> Result<AssertionArg, String> {
+    let (predicate, object) = s
+        .split_once('=')
+        .ok_or_else(|| "must be PREDICATE=OBJECT".to_string())?;
+    let assertion = AssertionArg {
+        predicate: AssertionPart::parse(predicate)?,
+        object: AssertionPart::parse(object)?,
+    };
+    if is_seed_assertion(&assertion.to_envelope()) {
+        return Err(
+            "the seed's type, name, note, and date are set with --name, --note, and --date"
+                .to_string(),
+        );
+    }
+    Ok(assertion)
+}
+
+/// An attachment to add to the seed's envelope.
+#[derive(Debug, Clone)]
+pub struct AttachmentArg {
+    pub vendor: String,
+    pub conforms_to: Option<String>,
+    pub payload: String,
+}
+
+/// Parses an attachment given as `vendor=VENDOR,[conforms-to=URI,]payload=
+/// PAYLOAD`. The payload comes last, so that it may contain commas.
+fn parse_attachment(s: &str) -> Result<AttachmentArg, String> {
+    let (fields, payload) = s
+        .split_once("payload=")
+        .ok_or_else(|| "must end with payload=PAYLOAD".to_string())?;
+    let (mut vendor, mut conforms_to) = (None, None);
+    for field in fields.split(',').filter(|field| !field.is_empty()) {
+        match field.split_once('=') {
+            Some(("vendor", value)) => vendor = Some(value.to_string()),
+            Some(("conforms-to", value)) => {
+                conforms_to = Some(value.to_string())
+            }
+            _ => return Err(format!("unknown field \"{}\"", field)),
+        }
+    }
+    Ok(AttachmentArg {
+        vendor: vendor.ok_or_else(|| "must have vendor=VENDOR".to_string())?,
+        conforms_to,
+        payload: payload.to_string(),
+    })
+}
+
+pub fn parse_date(s: &str) -> Result<Date, String> {
     if s == "now" {
         Ok(Date::now())
     } else {
@@ -47,13 +263,21 @@ pub struct Cli {
     #[arg(value_name = "INPUT")]
     pub input: Option<String>,
 
+    /// Prompt for the input on the terminal, with echo disabled, instead of
+    /// taking it from INPUT or stdin. For `bip39` input, each word is
+    /// prompted for separately, and may be abbreviated to its first four
+    /// letters. For `sskr` input, each share is prompted for separately and
+    /// checked as it is entered, until there are enough to recover the seed.
+    #[arg(long, conflicts_with_all = ["input", "batch", "batch_count"])]
+    pub prompt: bool,
+
     /// The number of output units (hex bytes, base-10 digits, etc.)
     #[arg(short, long, default_value_t = 16)]
     pub count: usize,
 
     /// The input format.
     /// If not specified, a new random seed is generated using a secure random
-    /// number generator.
+    /// number generator. Use `auto` to detect the format from the input.
     #[arg(
         value_enum,
         short,
@@ -64,14 +288,172 @@ pub struct Cli {
     pub r#in: InputFormatKey,
 
     /// The output format.
+    /// May appear more than once, or list several formats separated by
+    /// commas, to output the same seed in each format.
     #[arg(
         value_enum,
         short,
         long,
         value_name = "OUTPUT_TYPE",
-        default_value_t = OutputFormatKey::Hex,
+        value_delimiter = ',',
+        default_values_t = [OutputFormatKey::Hex],
+    )]
+    pub out: Vec<OutputFormatKey>,
+
+    /// Output a JSON document containing the seed's metadata and each
+    /// requested output, instead of plain text.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Write each output to its own file in DIR instead of printing it: one
+    /// file per SSKR share or multipart part, and one per other format. The
+    /// directory and files are readable only by the current user, and
+    /// existing files are never overwritten.
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["json", "batch", "batch_count", "bip39_recover", "sskr_inspect"]
+    )]
+    pub out_dir: Option<String>,
+
+    /// With `--out-dir`, also write `digests.json` listing the SHA-256
+    /// digest of each file written, without any of their contents.
+    #[arg(long, requires = "out_dir")]
+    pub digests: bool,
+
+    /// Output `seed` as the legacy `ur:crypto-seed` type, and SSKR `ur`
+    /// shares as the legacy `ur:crypto-sskr` type, for older wallets that
+    /// can't read the current types. No other output may be requested. A
+    /// legacy seed's date is stored as a day, without the time.
+    #[arg(long)]
+    pub legacy_ur: bool,
+
+    /// Encrypt `envelope` output with a password, prompted for twice on the
+    /// terminal. Only `envelope` output can be locked. The password is
+    /// stretched into a key with a memory-hard key derivation function, so
+    /// guessing it is slow. The result is a standard
+    /// password-locked envelope, whose metadata can't be read until it is
+    /// unlocked. `envelope` input locked with a password is always prompted
+    /// for it.
+    #[arg(help_heading = Some("Envelope Encryption"), long)]
+    pub envelope_password: bool,
+
+    /// The key derivation function used by `--envelope-password`. Argon2id
+    /// uses the fixed parameters of the envelope format (19 MiB of memory,
+    /// 2 passes), which can't be tuned; to tune the cost, use scrypt with
+    /// `--scrypt-log-n`, `--scrypt-r`, and `--scrypt-p`.
+    #[arg(
+        help_heading = Some("Envelope Encryption"),
+        value_enum,
+        long,
+        value_name = "KDF",
+        default_value_t = PasswordKdf::Argon2id,
+        requires = "envelope_password"
+    )]
+    pub envelope_kdf: PasswordKdf,
+
+    /// The base-2 logarithm of scrypt's cost, N (10-24). Each step doubles
+    /// the time and memory needed. Defaults to 15.
+    #[arg(
+        help_heading = Some("Envelope Encryption"),
+        long,
+        value_name = "LOG_N",
+        requires = "envelope_password"
+    )]
+    #[clap(value_parser = parse_scrypt_log_n)]
+    pub scrypt_log_n: Option<u8>,
+
+    /// scrypt's block size, r (1-32). The memory needed is 128 × N × r bytes,
+    /// which may be at most 4 GiB. Defaults to 8.
+    #[arg(
+        help_heading = Some("Envelope Encryption"),
+        long,
+        value_name = "R",
+        requires = "envelope_password"
     )]
-    pub out: OutputFormatKey,
+    #[clap(value_parser = parse_scrypt_r)]
+    pub scrypt_r: Option<u32>,
+
+    /// scrypt's parallelism, p (1-16), which multiplies the time needed.
+    /// Defaults to 1.
+    #[arg(
+        help_heading = Some("Envelope Encryption"),
+        long,
+        value_name = "P",
+        requires = "envelope_password"
+    )]
+    #[clap(value_parser = parse_scrypt_p)]
+    pub scrypt_p: Option<u32>,
+
+    /// Read the envelope password from the first line of FILE instead of
+    /// prompting for it.
+    #[arg(
+        help_heading = Some("Envelope Encryption"),
+        long,
+        value_name = "FILE"
+    )]
+    pub password_file: Option<String>,
+
+    /// Wrap and sign `envelope` and `multipart` output, and each SSKR share
+    /// envelope, with KEY: a `ur:signing-private-key` (e.g., Ed25519 or
+    /// Schnorr), or the signing key of a `ur:crypto-prvkeys` or
+    /// `ur:crypto-prvkey-base`, or the path of a file containing one. An
+    /// envelope locked with a password is signed after it is locked, so the
+    /// signature can be checked without the password.
+    #[arg(
+        help_heading = Some("Envelope Signatures"),
+        long,
+        value_name = "KEY"
+    )]
+    #[clap(value_parser = parse_signing_private_key_arg)]
+    pub signing_key: Option<KeyArg<SigningPrivateKey>>,
+
+    /// Require `envelope` and `multipart` input, each SSKR share, and the
+    /// envelope of `--envelope-check`, to be an envelope signed by KEY: a
+    /// `ur:signing-public-key`, or the signing key of a `ur:crypto-pubkeys`.
+    /// Without it, signed envelopes are accepted without checking their
+    /// signatures.
+    #[arg(
+        help_heading = Some("Envelope Signatures"),
+        long,
+        value_name = "KEY"
+    )]
+    #[clap(value_parser = parse_signing_public_key)]
+    pub verifying_key: Option<SigningPublicKey>,
+
+    /// Elide parts of `envelope` output, leaving only their digests, so the
+    /// envelope commits to them without revealing them: the `seed` itself,
+    /// its `name`, `note`, or `date`, or its other `assertions`, such as
+    /// attachments. May be given more than once, or as a comma-separated
+    /// list. Implies `--envelope-salt`. No other output may be requested, as
+    /// it would reveal what is elided.
+    #[arg(
+        help_heading = Some("Envelope Elision"),
+        value_enum,
+        long,
+        value_name = "PART",
+        value_delimiter = ','
+    )]
+    pub elide: Vec<ElidedPart>,
+
+    /// Add random salt to each assertion of `envelope` output, so that the
+    /// digests of short values, such as a name or a date, can't be matched
+    /// by guessing them once they are elided.
+    #[arg(help_heading = Some("Envelope Elision"), long)]
+    pub envelope_salt: bool,
+
+    /// Check that the seed is the one committed to by the seed envelope in
+    /// FILE, whose seed may be elided. Only the result is printed, not the
+    /// seed or any output format. The envelope's signature is checked
+    /// with `--verifying-key`, and a locked envelope is unlocked with the
+    /// envelope password.
+    #[arg(
+        help_heading = Some("Envelope Elision"),
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["batch", "batch_count", "bip39_recover", "sskr_inspect"]
+    )]
+    pub envelope_check: Option<String>,
 
     /// The lowest int returned (0-254)
     #[arg(
@@ -93,6 +475,96 @@ pub struct Cli {
     #[clap(value_parser = parse_high_int)]
     pub high: usize,
 
+    /// For `bip39` input, accept words abbreviated to their first four
+    /// letters. If the mnemonic is invalid, report which words are wrong and
+    /// suggest corrections, including single-word substitutions that give a
+    /// valid checksum.
+    #[arg(help_heading = Some("BIP-39 Input"), long)]
+    pub bip39_assist: bool,
+
+    /// For `bip39` input, take every word of a mnemonic but the last, and
+    /// list the final words that give a valid checksum on stderr. One of them
+    /// is chosen at random to complete the mnemonic; use `--deterministic`
+    /// to make the choice repeatable, or `--last-word-bits` to constrain it.
+    #[arg(
+        help_heading = Some("BIP-39 Input"),
+        long,
+        conflicts_with_all = ["bip39_assist", "bip39_recover"]
+    )]
+    pub bip39_last_word: bool,
+
+    /// The leading entropy bits of the last word, as a string of `0`s and
+    /// `1`s (e.g., from coin flips). If every entropy bit is given, only one
+    /// final word is possible.
+    #[arg(
+        help_heading = Some("BIP-39 Input"),
+        long,
+        value_name = "BITS",
+        default_value = "",
+        requires = "bip39_last_word"
+    )]
+    pub last_word_bits: String,
+
+    /// For `bip39` input, search for the mnemonics that could have been
+    /// intended. Mark each unknown word with `?`, or a word known to start
+    /// with certain letters as those letters followed by `?`. If there are
+    /// no placeholders, search for mnemonics with two words swapped. Every
+    /// candidate that passes the checksum is output.
+    #[arg(
+        help_heading = Some("BIP-39 Input"),
+        long,
+        conflicts_with_all = ["bip39_assist", "batch", "batch_count"]
+    )]
+    pub bip39_recover: bool,
+
+    /// Only output recovered mnemonics whose BIP-32 master key has this
+    /// fingerprint.
+    #[arg(
+        help_heading = Some("BIP-39 Input"),
+        long,
+        value_name = "HEX",
+        requires = "bip39_recover"
+    )]
+    #[clap(value_parser = parse_fingerprint)]
+    pub fingerprint: Option<[u8; 4]>,
+
+    /// Only output recovered mnemonics whose first receive address is
+    /// ADDRESS. Legacy (`1...`), nested SegWit (`3...`), and native SegWit
+    /// (`bc1q...`) addresses are supported, derived using BIP-44, BIP-49, and
+    /// BIP-84 respectively. Testnet addresses are also accepted.
+    #[arg(
+        help_heading = Some("BIP-39 Input"),
+        long,
+        value_name = "ADDRESS",
+        requires = "bip39_recover"
+    )]
+    pub address: Option<String>,
+
+    /// The BIP-39 passphrase used when matching `--fingerprint` or
+    /// `--address`, given as `--passphrase=PASSPHRASE`. Given without a
+    /// value, or with `--prompt`, it is prompted for on the terminal with
+    /// echo disabled.
+    #[arg(
+        help_heading = Some("BIP-39 Input"),
+        long,
+        value_name = "PASSPHRASE",
+        num_args = 0..=1,
+        require_equals = true,
+        requires = "bip39_recover"
+    )]
+    pub passphrase: Option<Option<String>>,
+
+    /// The number of threads used by `--bip39-recover`. Defaults to the
+    /// number of available CPUs.
+    #[arg(
+        help_heading = Some("BIP-39 Input"),
+        long,
+        value_name = "COUNT",
+        requires = "bip39_recover"
+    )]
+    #[clap(value_parser = parse_threads)]
+    pub threads: Option<usize>,
+
     /// The name of the seed.
     #[arg(help_heading = Some("Metadata"), long, value_name = "NAME")]
     pub name: Option<String>,
@@ -107,6 +579,38 @@ pub struct Cli {
     #[clap(value_parser = parse_date)]
     pub date: Option<Date>,
 
+    /// Drop any assertions and attachments of an `envelope` input seed other
+    /// than its name, note, and date. By default they are kept, and output
+    /// again by the formats that are envelopes.
+    #[arg(help_heading = Some("Metadata"), long)]
+    pub strip_assertions: bool,
+
+    /// Add an assertion to the seed's envelope, as PREDICATE=OBJECT. Each
+    /// side is a string, or a known value in single quotes, such as
+    /// `'dereferenceVia'`. May appear more than once.
+    #[arg(
+        help_heading = Some("Metadata"),
+        long,
+        value_name = "PREDICATE=OBJECT"
+    )]
+    #[clap(value_parser = parse_assertion)]
+    pub assertion: Vec<AssertionArg>,
+
+    /// Add an attachment to the seed's envelope, as
+    /// `vendor=VENDOR,conforms-to=URI,payload=PAYLOAD`: a string payload,
+    /// the vendor that defines it (e.g., a reverse domain name), and
+    /// optionally the URI of the format it conforms to. The payload comes
+    /// last, and may contain commas. May appear more than once.
+    #[arg(help_heading = Some("Metadata"), long, value_name = "ATTACHMENT")]
+    #[clap(value_parser = parse_attachment)]
+    pub attachment: Vec<AttachmentArg>,
+
+    /// Add an output descriptor (e.g.,
+    /// `wpkh([73c5da0a/84'/0'/0']xpub.../0/*)`) to the seed's envelope,
+    /// recording the wallet the seed is used with.
+    #[arg(help_heading = Some("Metadata"), long, value_name = "DESCRIPTOR")]
+    pub output_descriptor: Option<String>,
+
     /// For `multipart` output, the UR will be segmented into parts with
     /// fragments no larger than MAX_FRAG_LEN
     #[arg(
@@ -163,6 +667,117 @@ pub struct Cli {
     )]
     pub sskr_format: SSKRFormatKey,
 
+    /// After splitting the seed into SSKR shares, describe the recovery
+    /// policy, and verify that every minimal set of shares meeting it
+    /// recovers the seed and that no set falling short of it does. Requires
+    /// `sskr` output.
+    #[arg(help_heading = Some("SSKR Output"), long)]
+    pub sskr_verify: bool,
+
+    /// Carry the seed's name, note, and date in `btw`, `btwm`, `btwu`, and
+    /// `ur` SSKR shares, by splitting the seed's tagged CBOR instead of the
+    /// seed alone. They are recovered automatically, but SSKR can split at
+    /// most 32 bytes, leaving little room for metadata. Envelope shares always
+    /// carry it.
+    #[arg(help_heading = Some("SSKR Output"), long)]
+    pub sskr_metadata: bool,
+
+    /// Add labels to each SSKR share envelope from the JSON share manifest
+    /// FILE, saying who holds the share, which group and member it is, how
+    /// to recover the seed, and who to contact. Shares whose custodian has a
+    /// `recipient` public key are encrypted to it. Requires `sskr` output in
+    /// the `envelope` SSKR format.
+    #[arg(help_heading = Some("SSKR Output"), long, value_name = "FILE")]
+    pub sskr_manifest: Option<String>,
+
+    /// After splitting the seed into SSKR shares, write a distribution
+    /// manifest to FILE: an envelope recording the split's identifier and
+    /// policy, the digest and custodian of each share, the date, and the
+    /// digest of the seed, without revealing the seed or any share. Requires
+    /// `sskr` output.
+    #[arg(help_heading = Some("SSKR Output"), long, value_name = "FILE")]
+    pub sskr_distribution: Option<String>,
+
+    /// Sign the distribution manifest with this private key: a
+    /// `ur:crypto-prvkeys` or `ur:crypto-prvkey-base`, or the path of a file
+    /// containing one.
+    #[arg(
+        help_heading = Some("SSKR Output"),
+        long,
+        value_name = "KEY",
+        requires = "sskr_distribution"
+    )]
+    #[clap(value_parser = parse_private_keys_arg)]
+    pub sskr_distribution_signer: Option<KeyArg<PrivateKeys>>,
+
+    /// For `sskr` input, report what each share is and how the shares relate
+    /// to each other, without attempting to recover the seed.
+    #[arg(
+        help_heading = Some("SSKR Input"),
+        long,
+        conflicts_with_all = ["batch", "batch_count"]
+    )]
+    pub sskr_inspect: bool,
+
+    /// For `sskr` input, split the recovered seed again as SSKR shares using
+    /// `--groups` and `--group-threshold`, without ever outputting the seed
+    /// itself. The new split has a new identifier, and envelope shares keep
+    /// the seed's metadata.
+    #[arg(
+        help_heading = Some("SSKR Input"),
+        long,
+        requires = "groups",
+        conflicts_with_all = ["out", "sskr_inspect", "batch", "batch_count"]
+    )]
+    pub sskr_reshare: bool,
+
+    /// For `sskr` input, a private key that decrypts shares encrypted to a
+    /// custodian's public key: a `ur:crypto-prvkeys` or
+    /// `ur:crypto-prvkey-base`, or the path of a file containing one. May
+    /// appear more than once.
+    #[arg(
+        help_heading = Some("SSKR Input"),
+        long,
+        value_name = "KEY"
+    )]
+    #[clap(value_parser = parse_private_keys_arg)]
+    pub sskr_key: Vec<KeyArg<PrivateKeys>>,
+
+    /// For `sskr` input, check that each share is listed in the distribution
+    /// manifest in FILE, and that the recovered seed is the one it records.
+    #[arg(help_heading = Some("SSKR Input"), long, value_name = "FILE")]
+    pub sskr_distribution_check: Option<String>,
+
+    /// Require the distribution manifest to be signed by this public key,
+    /// given as a `ur:crypto-pubkeys`.
+    #[arg(
+        help_heading = Some("SSKR Input"),
+        long,
+        value_name = "UR",
+        requires = "sskr_distribution_check"
+    )]
+    #[clap(value_parser = parse_public_keys)]
+    pub sskr_distribution_verifier: Option<PublicKeys>,
+
+    /// Read inputs from FILE, one per line, and process each separately.
+    /// Use `-` to read from stdin. A line containing a JSON object is a
+    /// record with an `input`, and optionally a `name`, `note`, and `date`.
+    #[arg(
+        help_heading = Some("Batch Processing"),
+        long,
+        value_name = "FILE",
+        conflicts_with = "batch_count"
+    )]
+    pub batch: Option<String>,
+
+    /// Generate COUNT random seeds, processing each separately.
+    #[arg(
+        help_heading = Some("Batch Processing"),
+        long,
+        value_name = "COUNT"
+    )]
+    pub batch_count: Option<usize>,
+
     /// Use a deterministic random number generator with the given seed string.
     ///
     /// Output generated from this seed will be the same every time,
@@ -180,6 +795,14 @@ pub struct Cli {
 
     #[clap(skip)]
     pub rng: Option<RngSource>,
+
+    /// The identifiers of the SSKR splits the input shares came from.
+    #[clap(skip)]
+    pub sskr_input_identifiers: Vec<u16>,
+
+    /// The envelope password, once it has been read.
+    #[clap(skip)]
+    pub password: OnceCell<Zeroizing<String>>,
 }
 
 #[derive(Debug, Clone)]
@@ -189,14 +812,61 @@ pub enum RngSource {
 }
 
 impl Cli {
-    pub fn expect_input(&self) -> Result<String> {
-        if let Some(input) = &self.input {
-            Ok(input.clone())
+    /// Whether any private key was given on the command line rather than in
+    /// a file.
+    pub fn has_inline_private_key(&self) -> bool {
+        self.sskr_key
+            .iter()
+            .chain(&self.sskr_distribution_signer)
+            .any(|key| key.inline)
+            || self.signing_key.as_ref().is_some_and(|key| key.inline)
+    }
+
+    pub fn expect_input(&self) -> Result<Zeroizing<String>> {
+        if self.prompt {
+            prompt_secret("Input: ")
+        } else if let Some(input) = &self.input {
+            Ok(Zeroizing::new(input.clone()))
         } else {
-            let mut input = String::new();
+            let mut input = Zeroizing::new(String::new());
             io::stdin().read_to_string(&mut input)?;
-            Ok(input.trim().to_string())
+            Ok(Zeroizing::new(input.trim().to_string()))
+        }
+    }
+
+    /// The envelope password, read from `--password-file` or prompted for
+    /// the first time it is needed. When encrypting, `confirm` has it entered
+    /// twice.
+    pub fn password(&self, confirm: bool) -> Result<&str> {
+        if let Some(password) = self.password.get() {
+            return Ok(password);
         }
+        let password = if let Some(path) = &self.password_file {
+            let contents =
+                Zeroizing::new(fs::read_to_string(path).map_err(|e| {
+                    anyhow!("Can't read the password file \"{}\": {}", path, e)
+                })?);
+            Zeroizing::new(
+                contents
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            )
+        } else {
+            let password = prompt_secret("Envelope password: ")?;
+            if confirm
+                && *prompt_secret("Confirm envelope password: ")? != *password
+            {
+                bail!("The passwords don't match.");
+            }
+            password
+        };
+        if password.is_empty() {
+            bail!("The envelope password is empty.");
+        }
+        Ok(self.password.get_or_init(|| password))
     }
 
     pub fn expect_seed(&self) -> &Seed {
@@ -213,25 +883,52 @@ impl Cli {
         }
     }
 
-    pub fn seed_with_overrides(&self) -> Seed {
-        let mut seed = self.expect_seed().clone();
-        if let Some(name) = &self.name {
+    /// Applies `--name`, `--note`, `--date`, `--strip-assertions`, and the
+    /// added assertions, attachments, and output descriptor to the seed read
+    /// by the input format.
+    pub fn apply_overrides(&mut self) {
+        let (name, note, date) = (&self.name, &self.note, self.date);
+        let seed = self.seed.as_mut().expect("Seed not initialized");
+        if let Some(name) = name {
             seed.set_name(name);
         }
-        if let Some(note) = &self.note {
+        if let Some(note) = note {
             seed.set_note(note);
         }
-        if let Some(date) = self.date {
+        if let Some(date) = date {
             seed.set_creation_date(Some(date));
         }
-        seed
+        if self.strip_assertions {
+            seed.set_assertions(Vec::new());
+        }
+        for assertion in &self.assertion {
+            seed.add_assertion(assertion.to_envelope());
+        }
+        for attachment in &self.attachment {
+            seed.add_assertion(Envelope::new_attachment(
+                attachment.payload.as_str(),
+                &attachment.vendor,
+                attachment.conforms_to.as_deref(),
+            ));
+        }
+        if let Some(descriptor) = &self.output_descriptor {
+            seed.add_assertion(Envelope::new_assertion(
+                known_values::OUTPUT_DESCRIPTOR,
+                descriptor.as_str(),
+            ));
+        }
     }
 
-    pub fn to_envelope(&self) -> Envelope {
-        self.seed_with_overrides().into_envelope()
-    }
+    pub fn to_envelope(&self) -> Envelope { self.expect_seed().to_envelope() }
 
     pub fn sskr_spec(&self) -> Result<SSKRSpec> {
         Ok(SSKRSpec::new(self.group_threshold, self.groups.clone())?)
     }
 }
+
+impl Drop for Cli {
+    fn drop(&mut self) {
+        self.input.zeroize();
+        self.passphrase.zeroize();
+    }
+}
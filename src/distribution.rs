@@ -0,0 +1,207 @@
+//! A public manifest of an SSKR split: which shares were made, who holds them,
+//! and what they recover, without revealing the seed or any share.
+//!
+//! The manifest is an envelope whose subject is the elided envelope of the seed
+//! without its metadata, so it commits to the seed while revealing only its
+//! digest.
+//!
+//! ```text
+//! ELIDED [
+//!     'isA': "SSKRDistribution"
+//!     "groupThreshold": 2
+//!     "groups": ["2-of-3", "3-of-5"]
+//!     "policy": "any 2 of 3 from group 1 and 3 of 5 from group 2"
+//!     "share": Digest(...) [
+//!         "custodian": "Alice"
+//!         "group": 1
+//!         "member": 1
+//!     ]
+//!     ...
+//!     "sskrIdentifier": "41c1"
+//!     'date': 2026-10-19T06:43:00Z
+//! ]
+//! ```
+
+use std::{collections::HashMap, fs};
+
+use anyhow::{Result, anyhow, bail};
+use bc_components::{Digest, PrivateKeys, PublicKeys, SSKRShare};
+use bc_envelope::prelude::*;
+use sskr::Spec;
+
+use crate::{
+    files::write_file,
+    formats::{DecodedShare, describe_policy},
+    seed::Seed,
+    share_manifest::ShareManifest,
+};
+
+const DISTRIBUTION_TYPE: &str = "SSKRDistribution";
+
+/// The commitment to the seed that forms the manifest's subject. Metadata is
+/// left out, so that seeds recovered from shares that don't carry it still
+/// match.
+fn seed_commitment(seed: &Seed) -> Envelope {
+    Seed::new(seed.data()).to_envelope().elide()
+}
+
+fn share_digest(share: &SSKRShare) -> Digest {
+    Digest::from_image(share.as_bytes())
+}
+
+/// Makes the distribution manifest of a split, signed if a signer is given.
+pub fn make_distribution(
+    seed: &Seed,
+    spec: &Spec,
+    shares: &[(SSKRShare, String)],
+    manifest: Option<&ShareManifest>,
+    signer: Option<&PrivateKeys>,
+) -> Envelope {
+    let groups: Vec<String> = spec
+        .groups()
+        .iter()
+        .map(|group| {
+            format!("{}-of-{}", group.member_threshold(), group.member_count())
+        })
+        .collect();
+    let mut envelope = seed_commitment(seed)
+        .add_type(DISTRIBUTION_TYPE)
+        .add_assertion("sskrIdentifier", shares[0].0.identifier_hex())
+        .add_assertion("policy", describe_policy(spec))
+        .add_assertion("groupThreshold", spec.group_threshold())
+        .add_assertion("groups", groups);
+    for (share, _) in shares {
+        let custodian = manifest.and_then(|manifest| {
+            manifest
+                .labels(share.group_index(), share.member_index())
+                .custodian
+        });
+        let share_envelope = Envelope::new(share_digest(share))
+            .add_assertion("group", share.group_index() + 1)
+            .add_assertion("member", share.member_index() + 1)
+            .add_optional_assertion("custodian", custodian);
+        envelope = envelope.add_assertion("share", share_envelope);
+    }
+    envelope = envelope.add_assertion(known_values::DATE, Date::now());
+    match signer {
+        Some(signer) => envelope.sign(signer),
+        None => envelope,
+    }
+}
+
+/// Makes the distribution manifest and writes it to `path` as a UR.
+pub fn write_distribution(
+    path: &str,
+    seed: &Seed,
+    spec: &Spec,
+    shares: &[(SSKRShare, String)],
+    manifest: Option<&ShareManifest>,
+    signer: Option<&PrivateKeys>,
+) -> Result<()> {
+    let envelope = make_distribution(seed, spec, shares, manifest, signer);
+    write_file(
+        path.as_ref(),
+        format!("{}\n", envelope.ur_string()).as_bytes(),
+    )
+}
+
+/// A share listed in a distribution manifest.
+struct ListedShare {
+    group: usize,
+    member: usize,
+    custodian: Option<String>,
+}
+
+/// Reads a distribution manifest, checking its signature if a verifier is
+/// given. Returns the unsigned manifest.
+fn read_distribution(
+    path: &str,
+    verifier: Option<&PublicKeys>,
+) -> Result<Envelope> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        anyhow!("Can't read the distribution manifest \"{}\": {}", path, e)
+    })?;
+    let envelope = Envelope::from_ur_string(contents.trim())
+        .map_err(|e| anyhow!("The distribution manifest is invalid: {}", e))?;
+    let signed = !envelope
+        .assertions_with_predicate(known_values::SIGNED)
+        .is_empty();
+    let envelope = match (signed, verifier) {
+        (true, Some(verifier)) => envelope.verify(verifier).map_err(|_| {
+            anyhow!("The distribution manifest is not signed by the given key.")
+        })?,
+        (true, None) => {
+            eprintln!(
+                "Warning: The distribution manifest is signed, but the signature was not checked. Use --sskr-distribution-verifier to check it."
+            );
+            envelope.subject().try_unwrap()?
+        }
+        (false, Some(_)) => bail!("The distribution manifest is not signed."),
+        (false, None) => envelope,
+    };
+    if !envelope.has_type(DISTRIBUTION_TYPE) {
+        bail!("The envelope is not an SSKR distribution manifest.");
+    }
+    Ok(envelope)
+}
+
+/// Checks the shares presented, and the seed recovered from them, against a
+/// distribution manifest, describing each share on stderr. Fails if a share
+/// isn't listed or the seed doesn't match.
+pub fn check_distribution(
+    path: &str,
+    verifier: Option<&PublicKeys>,
+    shares: &[(usize, &DecodedShare)],
+    seed: &Seed,
+) -> Result<()> {
+    let envelope = read_distribution(path, verifier)?;
+    let identifier: String =
+        envelope.extract_object_for_predicate("sskrIdentifier")?;
+    let mut listed = HashMap::new();
+    for share in envelope.objects_for_predicate("share") {
+        let digest: Digest = share.extract_subject()?;
+        listed.insert(
+            digest,
+            ListedShare {
+                group: share.extract_object_for_predicate("group")?,
+                member: share.extract_object_for_predicate("member")?,
+                custodian: share
+                    .extract_optional_object_for_predicate("custodian")?,
+            },
+        );
+    }
+
+    for (line, decoded) in shares {
+        let share = &decoded.share;
+        if share.identifier_hex() != identifier {
+            bail!(
+                "The share on line {} is from split {}, but the distribution manifest is for split {}.",
+                line,
+                share.identifier_hex(),
+                identifier
+            );
+        }
+        let entry = listed.get(&share_digest(share)).ok_or_else(|| {
+            anyhow!(
+                "The share on line {} is not listed in the distribution manifest, so it may have been altered.",
+                line
+            )
+        })?;
+        let custodian = entry
+            .custodian
+            .as_ref()
+            .map(|custodian| format!(", held by {}", custodian))
+            .unwrap_or_default();
+        eprintln!(
+            "Line {}: listed as group {} member {}{}",
+            line, entry.group, entry.member, custodian
+        );
+    }
+    if envelope.subject().digest() != seed_commitment(seed).digest() {
+        bail!("The recovered seed does not match the distribution manifest.");
+    }
+    eprintln!(
+        "The shares and the recovered seed match the distribution manifest."
+    );
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! Writing each output to its own file, so that SSKR shares and multipart
+//! parts can be handed out separately instead of all being printed together.
+
+use std::{
+    fs::{self, DirBuilder, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow, bail};
+use bc_crypto::sha256;
+use serde_json::json;
+use zeroize::Zeroizing;
+
+use crate::{cli::Cli, formats::OutputFormat, output::JSON_SCHEMA_VERSION};
+
+/// The file listing the digest of each file written.
+const DIGESTS_FILE_NAME: &str = "digests.json";
+
+/// Creates the directory if needed, readable only by the current user.
+fn create_dir(dir: &Path) -> Result<()> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir).map_err(|e| {
+        anyhow!("Can't create the directory \"{}\": {}", dir.display(), e)
+    })
+}
+
+/// Writes a new file readable only by the current user, failing if it
+/// already exists. If writing fails, the new file is removed.
+pub fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| anyhow!("Can't create \"{}\": {}", path.display(), e))?;
+    if let Err(e) = file.write_all(contents).and_then(|_| file.sync_all()) {
+        let _ = fs::remove_file(path);
+        bail!("Can't write \"{}\": {}", path.display(), e);
+    }
+    Ok(())
+}
+
+/// Writes each file to the directory, returning their paths. If one can't be
+/// written, those already written are removed, so that a failure doesn't
+/// leave a partial set of shares behind.
+fn write_files(
+    dir: &Path,
+    files: &[(String, Zeroizing<String>)],
+) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for (name, contents) in files {
+        let path = dir.join(name);
+        if let Err(e) = write_file(&path, contents.as_bytes()) {
+            for path in &written {
+                let _ = fs::remove_file(path);
+            }
+            return Err(e);
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Writes the files of every output format to the directory, printing the
+/// path of each. All the outputs are generated before any file is written,
+/// and the files are removed again if any of them can't be written.
+pub fn write_output_files(
+    state: &Cli,
+    dir: &str,
+    output_formats: &[Box<dyn OutputFormat>],
+) -> Result<()> {
+    let files = output_formats
+        .iter()
+        .map(|output_format| {
+            Ok(output_format
+                .process_output_files(state)?
+                .into_iter()
+                .map(|(name, contents)| {
+                    (
+                        name,
+                        output_format.name().to_string(),
+                        Zeroizing::new(contents),
+                    )
+                })
+                .collect::<Vec<_>>())
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let dir = Path::new(dir);
+    create_dir(dir)?;
+    let existing = files
+        .iter()
+        .map(|(name, _, _)| name.as_str())
+        .chain(state.digests.then_some(DIGESTS_FILE_NAME))
+        .find(|name| dir.join(name).exists());
+    if let Some(name) = existing {
+        bail!(
+            "\"{}\" already exists, and won't be overwritten.",
+            dir.join(name).display()
+        );
+    }
+
+    let mut digests = Vec::new();
+    let mut contents = Vec::new();
+    for (name, format, output) in &files {
+        let output = Zeroizing::new(format!("{}\n", output.as_str()));
+        digests.push(json!({
+            "file": name,
+            "format": format,
+            "sha256": hex::encode(sha256(output.as_bytes())),
+        }));
+        contents.push((name.clone(), output));
+    }
+    if state.digests {
+        let document = json!({
+            "version": JSON_SCHEMA_VERSION,
+            "files": digests,
+        });
+        contents.push((
+            DIGESTS_FILE_NAME.to_string(),
+            Zeroizing::new(format!("{:#}\n", document)),
+        ));
+    }
+    for path in write_files(dir, &contents)? {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file =
+            |name: &str| (name.to_string(), Zeroizing::new("share".into()));
+
+        // The second file can't be created, so the first is removed.
+        let files = [file("a.txt"), file("missing/b.txt")];
+        assert!(write_files(dir.path(), &files).is_err());
+        assert!(!dir.path().join("a.txt").exists());
+
+        let files = [file("a.txt"), file("b.txt")];
+        let written = write_files(dir.path(), &files).unwrap();
+        assert_eq!(
+            written,
+            vec![dir.path().join("a.txt"), dir.path().join("b.txt")]
+        );
+        assert_eq!(fs::read_to_string(&written[1]).unwrap(), "share");
+    }
+}
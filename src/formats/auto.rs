@@ -0,0 +1,260 @@
+use std::mem;
+
+use anyhow::{Result, bail};
+use bc_components::tags;
+use bc_envelope::prelude::*;
+use bip39::Mnemonic;
+use clap::ValueEnum;
+
+use super::{
+    Format, InputFormat, InputFormatKey, select_input_format, unwrap_signed,
+};
+use crate::cli::Cli;
+
+pub struct AutoFormat;
+
+impl Format for AutoFormat {
+    fn name(&self) -> &str { "auto" }
+
+    fn round_trippable(&self) -> bool { true }
+}
+
+impl InputFormat for AutoFormat {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
+        let mut input = state.expect_input()?;
+        let key = detect_input_format(&input)?;
+        let format = select_input_format(key);
+        eprintln!("Detected input format: {}", format.name());
+        // The input has already been read, so the detected format must not
+        // prompt for it again.
+        state.prompt = false;
+        state.input = Some(mem::take(&mut *input));
+        format.process_input(state)
+    }
+}
+
+/// Determines which input format the given text is encoded in.
+///
+/// Fails if the text matches no supported format, or if it matches more than
+/// one.
+pub fn detect_input_format(input: &str) -> Result<InputFormatKey> {
+    let input = input.trim();
+    if input.is_empty() {
+        bail!("No input to detect.");
+    }
+
+    let mut candidates = Vec::new();
+    if input
+        .split_whitespace()
+        .any(|s| s.to_lowercase().starts_with("ur:"))
+    {
+        candidates.push(detect_ur_format(input)?);
+    } else {
+        if hex::decode(input).is_ok() {
+            candidates.push(InputFormatKey::Hex);
+        }
+        for (style, key) in [
+            (bytewords::Style::Standard, InputFormatKey::Btw),
+            (bytewords::Style::Uri, InputFormatKey::Btwu),
+            (bytewords::Style::Minimal, InputFormatKey::Btwm),
+        ] {
+            if let Some(key) = detect_bytewords_format(input, style, key)
+                && !candidates.contains(&key)
+            {
+                candidates.push(key);
+            }
+        }
+        if Mnemonic::parse_normalized(input).is_ok() {
+            candidates.push(InputFormatKey::Bip39);
+        }
+    }
+
+    match candidates.as_slice() {
+        [] => bail!("Unable to detect the input format."),
+        [key] => Ok(*key),
+        keys => bail!(
+            "Ambiguous input: could be any of {}. Use --in to choose.",
+            keys.iter()
+                .map(format_key_name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn format_key_name(key: &InputFormatKey) -> String {
+    key.to_possible_value().unwrap().get_name().to_string()
+}
+
+fn detect_bytewords_format(
+    input: &str,
+    style: bytewords::Style,
+    key: InputFormatKey,
+) -> Option<InputFormatKey> {
+    // Standard bytewords include spaces, so each share is on its own line.
+    let strings: Vec<&str> = match style {
+        bytewords::Style::Standard => input
+            .lines()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        _ => input.split_whitespace().collect(),
+    };
+    let decoded = strings
+        .iter()
+        .map(|s| bytewords::decode(s, style))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    if decoded.iter().all(|data| is_tagged_sskr_share(data)) {
+        Some(InputFormatKey::Sskr)
+    } else if decoded.len() == 1 {
+        Some(key)
+    } else {
+        None
+    }
+}
+
+fn is_tagged_sskr_share(data: &[u8]) -> bool {
+    CBOR::try_from_data(data)
+        .and_then(|cbor| {
+            cbor.try_into_expected_tagged_value(tags::TAG_SSKR_SHARE)
+        })
+        .is_ok()
+}
+
+fn is_multipart_ur(string: &str) -> bool {
+    let components: Vec<&str> = string.split('/').collect();
+    components.len() == 3
+        && components[1].split_once('-').is_some_and(|(seq, len)| {
+            seq.parse::<usize>().is_ok() && len.parse::<usize>().is_ok()
+        })
+}
+
+fn detect_ur_format(input: &str) -> Result<InputFormatKey> {
+    // Anything that isn't a UR (e.g., a label on its own line) is ignored.
+    let strings: Vec<&str> = input
+        .split_whitespace()
+        .filter(|s| s.to_lowercase().starts_with("ur:"))
+        .collect();
+    if strings.iter().any(|s| is_multipart_ur(s)) {
+        return Ok(InputFormatKey::Multipart);
+    }
+
+    let mut keys = Vec::new();
+    for string in strings.iter() {
+        let ur = UR::from_ur_string(*string)?;
+        let key = match ur.ur_type_str() {
+            "seed" | "crypto-seed" => InputFormatKey::Seed,
+            "sskr" | "crypto-sskr" => InputFormatKey::Sskr,
+            "envelope" => {
+                let envelope = unwrap_signed(Envelope::from_ur(&ur)?, None)?;
+                if envelope
+                    .assertions_with_predicate(known_values::SSKR_SHARE)
+                    .is_empty()
+                {
+                    InputFormatKey::Envelope
+                } else {
+                    InputFormatKey::Sskr
+                }
+            }
+            ur_type => bail!("Unsupported UR type: {}", ur_type),
+        };
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    match keys.as_slice() {
+        [InputFormatKey::Sskr] => Ok(InputFormatKey::Sskr),
+        [key] if strings.len() == 1 => Ok(*key),
+        [key] => bail!(
+            "Expected a single UR for input format \"{}\".",
+            format_key_name(key)
+        ),
+        _ => bail!("Input contains URs of more than one kind."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        bc_envelope::register_tags();
+
+        let cases = [
+            ("9d347f841a4e2ce6bc886e1aee74d824", InputFormatKey::Hex),
+            (
+                "next edge lamb liar city girl draw visa roof logo jolt city waxy jury trip dark loud duty obey monk",
+                InputFormatKey::Btw,
+            ),
+            (
+                "next-edge-lamb-liar-city-girl-draw-visa-roof-logo-jolt-city-waxy-jury-trip-dark-loud-duty-obey-monk",
+                InputFormatKey::Btwu,
+            ),
+            (
+                "nteelblrcygldwvarflojtcywyjytpdklddyoymk",
+                InputFormatKey::Btwm,
+            ),
+            (
+                "outdoor physical three cruel tissue infant vendor assist brain inhale current emotion",
+                InputFormatKey::Bip39,
+            ),
+            (
+                "ur:seed/oyadgdnteelblrcygldwvarflojtcywyjytpdkjspafltb",
+                InputFormatKey::Seed,
+            ),
+            (
+                "ur:envelope/lptpsogdnteelblrcygldwvarflojtcywyjytpdkoyadcsspoyaatpsojoghisinjkcxinjkcxjyisihcxjtjljyihoybdtpsoisguihihieglhsjnihoybetpsosecyiyjzvsayehspswda",
+                InputFormatKey::Envelope,
+            ),
+            (
+                "ur:crypto-sskr/taadecgomymwbyadaenndtrehegwjkktoljphehtkshhbnhgiofmsebabs",
+                InputFormatKey::Sskr,
+            ),
+            (
+                "ur:envelope/1-3/lpadaxcscscylehgbwuofdlftpsogdnteelblraahdvtpt",
+                InputFormatKey::Multipart,
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                detect_input_format(input).unwrap(),
+                expected,
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_detect_sskr_envelopes() {
+        bc_envelope::register_tags();
+
+        #[rustfmt::skip]
+        let input = indoc!("
+            from group 1
+            ur:envelope/lftansfwlrhdcebzgtdmuoasfwjnnyiocfwtiorsrnyazeathtsowloxdsamiagssffxvlgsfrbbhelbetvtlowntksgahrygdkissoygsgypkkgrfvlcllofrlantrdwnhddatansfphdcxlultemsglryauraaesnblndnfglbihmsehtbfsehlsroptkgswdyvdpkmyhpwynnoyamtpsotantkphddazslpadadaeayjpeefensrfbznsnnswzswtynsaurbaiewmnesfwlvefhwylksrhfjpnectjzhdgturmkfr
+            ur:envelope/lftansfwlrhdcebzgtdmuoasfwjnnyiocfwtiorsrnyazeathtsowloxdsamiagssffxvlgsfrbbhelbetvtlowntksgahrygdkissoygsgypkkgrfvlcllofrlantrdwnhddatansfphdcxlultemsglryauraaesnblndnfglbihmsehtbfsehlsroptkgswdyvdpkmyhpwynnoyamtpsotantkphddazslpadadadkndebdkifwghutmseolfbagltdkodyuevofwbncxhsbegltiskzowljzlkfzuotertatahwk
+        ");
+        assert_eq!(detect_input_format(input).unwrap(), InputFormatKey::Sskr);
+    }
+
+    #[test]
+    fn test_detect_failures() {
+        bc_envelope::register_tags();
+
+        assert!(detect_input_format("").is_err());
+        assert!(detect_input_format("not a seed").is_err());
+        assert!(detect_input_format("ur:digest/hdcxlkahssqzwfvslofzoxwkrewngotktbmwjkwdcmnefsaaehrlolkskncnktlbaypkvoonhknt").is_err());
+        // Valid as both hex and Bytewords Minimal.
+        let err = detect_input_format("cedacabaecdebebdce").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Ambiguous input: could be any of hex, btwm. Use --in to choose."
+        );
+    }
+}
@@ -1,8 +1,14 @@
 use anyhow::Result;
 use bip39::Mnemonic;
+use zeroize::Zeroizing;
 
 use super::{Format, InputFormat, OutputFormat};
-use crate::{cli::Cli, seed::Seed};
+use crate::{
+    cli::Cli,
+    mnemonic::{last_words, parse_with_assist},
+    prompt::prompt_bip39_words,
+    seed::Seed,
+};
 
 pub struct Bip39Format;
 
@@ -13,15 +19,53 @@ impl Format for Bip39Format {
 }
 
 impl InputFormat for Bip39Format {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
-        let mnemonic = Mnemonic::parse_normalized(&state.expect_input()?)?;
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
+        let input = if state.prompt && !state.bip39_last_word {
+            prompt_bip39_words()?
+        } else {
+            state.expect_input()?
+        };
+        let mnemonic = if state.bip39_last_word {
+            complete_last_word(state, &input)?
+        } else if state.bip39_assist {
+            parse_with_assist(&input)?
+        } else {
+            Mnemonic::parse_normalized(&input)?
+        };
         state.seed = Some(Seed::new(mnemonic.to_entropy()));
-        Ok(state)
+        Ok(())
     }
 }
 
+/// Completes a mnemonic missing its last word, explaining the choice on
+/// stderr.
+fn complete_last_word(state: &mut Cli, input: &str) -> Result<Mnemonic> {
+    let mut last = last_words(input, &state.last_word_bits)?;
+    eprintln!(
+        "The last word of a {}-word mnemonic holds {} entropy bits and {} checksum bits. {} of the entropy bits were given, so {} final word(s) give a valid checksum:",
+        last.words.len() + 1,
+        last.entropy_bits,
+        last.checksum_bits,
+        last.given_bits,
+        last.choices.len()
+    );
+    eprintln!("{}", last.choices.join(" "));
+    let word = if last.choices.len() == 1 {
+        last.choices[0]
+    } else {
+        eprintln!("Choosing one of them at random.");
+        let random = state.random_data(2);
+        let n = u16::from_be_bytes([random[0], random[1]]) as usize;
+        // The number of choices is a power of two, so this is unbiased.
+        last.choices[n % last.choices.len()]
+    };
+    last.words.push(word);
+    let mnemonic = Zeroizing::new(last.words.join(" "));
+    Ok(Mnemonic::parse_normalized(&mnemonic)?)
+}
+
 impl OutputFormat for Bip39Format {
-    fn process_output(&self, state: Cli) -> Result<String> {
+    fn process_output(&self, state: &Cli) -> Result<String> {
         let mnemonic = Mnemonic::from_entropy(state.expect_seed().data())?;
         let words = mnemonic.words().collect::<Vec<&str>>().join(" ");
         Ok(words)
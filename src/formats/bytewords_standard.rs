@@ -13,17 +13,17 @@ impl Format for BytewordsStandardFormat {
 }
 
 impl InputFormat for BytewordsStandardFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         state.seed = Some(Seed::new(bytewords::decode(
             &state.expect_input()?,
             bytewords::Style::Standard,
         )?));
-        Ok(state)
+        Ok(())
     }
 }
 
 impl OutputFormat for BytewordsStandardFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
+    fn process_output(&self, state: &Cli) -> Result<String> {
         Ok(bytewords::encode(
             state.expect_seed().data(),
             bytewords::Style::Standard,
@@ -13,17 +13,17 @@ impl Format for BytewordsUriFormat {
 }
 
 impl InputFormat for BytewordsUriFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         state.seed = Some(Seed::new(bytewords::decode(
             &state.expect_input()?,
             bytewords::Style::Uri,
         )?));
-        Ok(state)
+        Ok(())
     }
 }
 
 impl OutputFormat for BytewordsUriFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
+    fn process_output(&self, state: &Cli) -> Result<String> {
         Ok(bytewords::encode(
             state.expect_seed().data(),
             bytewords::Style::Uri,
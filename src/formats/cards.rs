@@ -14,16 +14,16 @@ impl Format for CardsFormat {
 }
 
 impl InputFormat for CardsFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         let entropy = cards_to_data(&state.expect_input()?)?;
         let seed = Seed::new(deterministic_random(&entropy, state.count));
         state.seed = Some(seed);
-        Ok(state)
+        Ok(())
     }
 }
 
 impl OutputFormat for CardsFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
+    fn process_output(&self, state: &Cli) -> Result<String> {
         Ok(data_to_alphabet(state.expect_seed().data(), 52, to_card))
     }
 }
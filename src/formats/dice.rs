@@ -17,7 +17,7 @@ impl Format for DiceFormat {
 }
 
 impl InputFormat for DiceFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         // Compatibility with https://iancoleman.io/bip39/
         let string = state.expect_input()?;
         digits_to_data(&string, 1, 6)?; // syntax check only
@@ -25,12 +25,12 @@ impl InputFormat for DiceFormat {
             &string,
             state.count,
         )?));
-        Ok(state)
+        Ok(())
     }
 }
 
 impl OutputFormat for DiceFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
+    fn process_output(&self, state: &Cli) -> Result<String> {
         data_to_ints(state.expect_seed().data(), 1, 6, "")
     }
 }
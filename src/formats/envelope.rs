@@ -1,8 +1,22 @@
-use anyhow::Result;
+use std::{collections::HashSet, fs};
+
+use anyhow::{Result, anyhow, bail};
+use bc_components::{
+    Argon2idParams, EncryptedKey, KeyDerivationParams, Salt, ScryptParams,
+    SigningPublicKey, SymmetricKey,
+};
 use bc_envelope::prelude::*;
+use clap::ValueEnum;
 
 use super::{Format, InputFormat, OutputFormat};
-use crate::{cli::Cli, seed::Seed};
+use crate::{
+    cli::Cli,
+    seed::{Seed, is_seed_assertion},
+};
+
+/// The length of the salt used in key derivation, matching the envelope
+/// format's own.
+const SALT_LEN: usize = 16;
 
 pub struct EnvelopeFormat;
 
@@ -13,16 +27,268 @@ impl Format for EnvelopeFormat {
 }
 
 impl InputFormat for EnvelopeFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         let string = state.expect_input()?;
-        let envelope = Envelope::from_ur_string(string)?;
+        let envelope = Envelope::from_ur_string(string.as_str())?;
+        let envelope = open(envelope, state)?;
+        if envelope.subject().is_elided() {
+            bail!(
+                "The seed is elided from the envelope. Use --envelope-check to check a seed against it."
+            );
+        }
         state.seed = Some(Seed::try_from(envelope)?);
-        Ok(state)
+        Ok(())
     }
 }
 
 impl OutputFormat for EnvelopeFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
-        Ok(state.to_envelope().ur_string())
+    fn process_output(&self, state: &Cli) -> Result<String> {
+        let mut envelope = state.to_envelope();
+        if state.envelope_salt || !state.elide.is_empty() {
+            envelope = salt_assertions(&envelope);
+        }
+        if !state.elide.is_empty() {
+            envelope = elide(&envelope, &state.elide);
+        }
+        if state.envelope_password {
+            let params = key_derivation_params(state)?;
+            envelope = lock(&envelope, params, state.password(true)?)?;
+        }
+        if let Some(signing_key) = &state.signing_key {
+            envelope = envelope.sign(&**signing_key);
+        }
+        Ok(envelope.ur_string())
+    }
+}
+
+/// Checks the signature of a seed envelope and unlocks it, as given by the
+/// options.
+fn open(envelope: Envelope, state: &Cli) -> Result<Envelope> {
+    let envelope = unwrap_signed(envelope, state.verifying_key.as_ref())?;
+    if !envelope.is_locked_with_password() {
+        return Ok(envelope);
+    }
+    envelope
+        .unlock(state.password(false)?)
+        .map_err(|_| anyhow!("The envelope password is incorrect."))
+}
+
+/// Removes the signature from a signed envelope, checking it if a verifier is
+/// given. An unsigned envelope is returned as it is, unless a verifier is
+/// given.
+pub fn unwrap_signed(
+    envelope: Envelope,
+    verifier: Option<&SigningPublicKey>,
+) -> Result<Envelope> {
+    let signed = !envelope
+        .assertions_with_predicate(known_values::SIGNED)
+        .is_empty();
+    match (signed, verifier) {
+        (true, Some(verifier)) => envelope.verify(verifier).map_err(|_| {
+            anyhow!("The envelope is not signed by the given key.")
+        }),
+        (true, None) => Ok(envelope.subject().try_unwrap()?),
+        (false, Some(_)) => bail!("The envelope is not signed."),
+        (false, None) => Ok(envelope),
+    }
+}
+
+/// The parts of a seed envelope that can be elided.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ElidedPart {
+    Seed,
+    Name,
+    Note,
+    Date,
+    /// The assertions other than the seed's type, name, note, and date.
+    Assertions,
+}
+
+impl ElidedPart {
+    fn includes(&self, assertion: &Envelope) -> bool {
+        let predicate = match self {
+            Self::Seed => return false,
+            Self::Name => known_values::NAME,
+            Self::Note => known_values::NOTE,
+            Self::Date => known_values::DATE,
+            Self::Assertions => return !is_seed_assertion(assertion),
+        };
+        assertion
+            .as_predicate()
+            .is_some_and(|p| p.as_known_value() == Some(&predicate))
+    }
+}
+
+/// Adds salt to each assertion of the envelope that doesn't already have
+/// it, so that its digest can't be found by guessing its contents.
+fn salt_assertions(envelope: &Envelope) -> Envelope {
+    envelope.assertions().into_iter().fold(
+        envelope.subject(),
+        |salted, assertion| {
+            let unsalted = assertion
+                .assertions_with_predicate(known_values::SALT)
+                .is_empty();
+            salted
+                .add_assertion_envelope_salted(assertion, unsalted)
+                .expect("assertions of an envelope are assertions")
+        },
+    )
+}
+
+/// Elides the given parts of a seed envelope, leaving their digests.
+fn elide(envelope: &Envelope, parts: &[ElidedPart]) -> Envelope {
+    let mut target = HashSet::new();
+    if parts.contains(&ElidedPart::Seed) {
+        target.insert(envelope.subject().digest());
+    }
+    for assertion in envelope.assertions() {
+        if parts.iter().any(|part| part.includes(&assertion)) {
+            target.insert(assertion.digest());
+        }
+    }
+    envelope.elide_removing_set(&target)
+}
+
+/// Checks that the seed is the one committed to by the seed envelope in the
+/// file at `path`, comparing the digests of their subjects, so that the
+/// envelope's seed may be elided. Only the result is printed, never the seed.
+pub fn check_envelope(path: &str, state: &Cli) -> Result<()> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Can't read the envelope \"{}\": {}", path, e))?;
+    let envelope = Envelope::from_ur_string(contents.trim())
+        .map_err(|e| anyhow!("The envelope is invalid: {}", e))?;
+    let envelope = open(envelope, state)?;
+    if !envelope.has_type_value(&known_values::SEED_TYPE) {
+        bail!("The envelope is not a seed envelope.");
+    }
+    if envelope.subject().digest()
+        != state.expect_seed().to_envelope().subject().digest()
+    {
+        bail!("The seed does not match the envelope.");
+    }
+    println!("The seed matches the envelope.");
+    Ok(())
+}
+
+/// The most memory scrypt may be asked for: 4 GiB.
+const MAX_SCRYPT_MEMORY: u64 = 4 << 30;
+
+/// The memory-hard key derivation functions a password can be stretched with.
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PasswordKdf {
+    Argon2id,
+    Scrypt,
+}
+
+fn key_derivation_params(state: &Cli) -> Result<KeyDerivationParams> {
+    let salt = Salt::new_with_len(SALT_LEN)?;
+    match state.envelope_kdf {
+        PasswordKdf::Argon2id => {
+            if state.scrypt_log_n.is_some()
+                || state.scrypt_r.is_some()
+                || state.scrypt_p.is_some()
+            {
+                bail!("The scrypt parameters require --envelope-kdf scrypt.");
+            }
+            Ok(KeyDerivationParams::Argon2id(Argon2idParams::new_opt(salt)))
+        }
+        PasswordKdf::Scrypt => {
+            let log_n = state.scrypt_log_n.unwrap_or(15);
+            let r = state.scrypt_r.unwrap_or(8);
+            let p = state.scrypt_p.unwrap_or(1);
+            // scrypt requires N < 2^(16r).
+            if log_n as u32 >= 16 * r {
+                bail!(
+                    "--scrypt-log-n must be less than {} when --scrypt-r is {}.",
+                    16 * r,
+                    r
+                );
+            }
+            // scrypt needs 128 × N × r bytes, which can't be allocated
+            // beyond a few GiB.
+            let memory = (128u64 << log_n) * r as u64;
+            if memory > MAX_SCRYPT_MEMORY {
+                bail!(
+                    "The scrypt parameters need {} MiB of memory, more than the limit of {} MiB. Lower --scrypt-log-n or --scrypt-r.",
+                    memory >> 20,
+                    MAX_SCRYPT_MEMORY >> 20
+                );
+            }
+            Ok(KeyDerivationParams::Scrypt(ScryptParams::new_opt(
+                salt, log_n, r, p,
+            )))
+        }
+    }
+}
+
+/// Wraps the envelope and encrypts it with a key locked by the password, in
+/// the same form as `Envelope::lock`, but with the given key derivation
+/// parameters.
+fn lock(
+    envelope: &Envelope,
+    params: KeyDerivationParams,
+    password: &str,
+) -> Result<Envelope> {
+    let content_key = SymmetricKey::new();
+    let encrypted_key = EncryptedKey::lock_opt(params, password, &content_key)?;
+    Ok(envelope
+        .wrap()
+        .encrypt_subject(&content_key)?
+        .add_assertion(known_values::HAS_SECRET, encrypted_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elide() {
+        let seed = Seed::new_opt(
+            hex::decode("9d347f841a4e2ce6bc886e1aee74d824").unwrap(),
+            "Alice",
+            "Cold storage",
+            None,
+        );
+        let envelope = salt_assertions(&seed.to_envelope());
+        assert_eq!(envelope.digest(), salt_assertions(&envelope).digest());
+        assert_ne!(
+            envelope.digest(),
+            salt_assertions(&seed.to_envelope()).digest()
+        );
+
+        let elided = elide(&envelope, &[ElidedPart::Seed, ElidedPart::Name]);
+        assert_eq!(elided.digest(), envelope.digest());
+        assert!(elided.subject().is_elided());
+        assert_eq!(
+            elided.subject().digest(),
+            seed.to_envelope().subject().digest()
+        );
+        assert!(elided.assertion_with_predicate(known_values::NAME).is_err());
+        let note: String = elided
+            .extract_object_for_predicate(known_values::NOTE)
+            .unwrap();
+        assert_eq!(note, "Cold storage");
+    }
+
+    #[test]
+    fn test_lock() {
+        let seed = Seed::new_opt(
+            hex::decode("9d347f841a4e2ce6bc886e1aee74d824").unwrap(),
+            "Alice",
+            "",
+            None,
+        );
+        let params = KeyDerivationParams::Scrypt(ScryptParams::new_opt(
+            Salt::new_with_len(SALT_LEN).unwrap(),
+            10,
+            8,
+            1,
+        ));
+        let locked =
+            lock(&seed.to_envelope(), params, "correct horse").unwrap();
+        assert!(locked.is_locked_with_password());
+        assert!(locked.unlock("wrong horse").is_err());
+        let unlocked = locked.unlock("correct horse").unwrap();
+        assert_eq!(Seed::try_from(unlocked).unwrap(), seed);
     }
 }
@@ -1,11 +1,12 @@
 use anyhow::Result;
 use clap::ValueEnum;
+use serde_json::{Value, json};
 
 use super::{
-    Base6Format, Base10Format, Bip39Format, BitsFormat, BytewordsMinimalFormat,
-    BytewordsStandardFormat, BytewordsUriFormat, CardsFormat, DiceFormat,
-    EnvelopeFormat, HexFormat, IntsFormat, MultipartFormat, RandomFormat,
-    SSKRFormat, SeedFormat,
+    AutoFormat, Base6Format, Base10Format, Bip39Format, BitsFormat,
+    BytewordsMinimalFormat, BytewordsStandardFormat, BytewordsUriFormat,
+    CardsFormat, DiceFormat, EnvelopeFormat, HexFormat, IntsFormat,
+    MultipartFormat, RandomFormat, SSKRFormat, SeedFormat,
 };
 use crate::cli::Cli;
 
@@ -15,16 +16,40 @@ pub trait Format {
 }
 
 pub trait InputFormat: Format {
-    fn process_input(&self, state: Cli) -> Result<Cli>;
+    fn process_input(&self, state: &mut Cli) -> Result<()>;
 }
 
 pub trait OutputFormat: Format {
-    fn process_output(&self, _state: Cli) -> Result<String>;
+    fn process_output(&self, state: &Cli) -> Result<String>;
+
+    /// The output as an entry in the `outputs` array of the JSON document.
+    /// Formats that produce several strings (e.g., SSKR shares) override this
+    /// to describe each one.
+    fn process_output_json(&self, state: &Cli) -> Result<Value> {
+        Ok(json!({
+            "format": self.name(),
+            "value": self.process_output(state)?,
+        }))
+    }
+
+    /// The output as files for `--out-dir`, each paired with its file name.
+    /// Formats that produce several strings override this to write each to
+    /// its own file, so that they can be handed out separately.
+    fn process_output_files(
+        &self,
+        state: &Cli,
+    ) -> Result<Vec<(String, String)>> {
+        Ok(vec![(
+            format!("{}.txt", self.name()),
+            self.process_output(state)?,
+        )])
+    }
 }
 
 #[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum InputFormatKey {
     Random,
+    Auto,
     Hex,
     Btw,
     Btwu,
@@ -47,6 +72,7 @@ pub fn select_input_format(
 ) -> Box<dyn InputFormat> {
     match input_format {
         InputFormatKey::Random => Box::new(RandomFormat),
+        InputFormatKey::Auto => Box::new(AutoFormat),
         InputFormatKey::Hex => Box::new(HexFormat),
         InputFormatKey::Btw => Box::new(BytewordsStandardFormat),
         InputFormatKey::Btwu => Box::new(BytewordsUriFormat),
@@ -12,16 +12,16 @@ impl Format for HexFormat {
 }
 
 impl InputFormat for HexFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         let input = state.expect_input()?;
         let seed = Seed::new(hex::decode(input)?);
         state.seed = Some(seed);
-        Ok(state)
+        Ok(())
     }
 }
 
 impl OutputFormat for HexFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
+    fn process_output(&self, state: &Cli) -> Result<String> {
         Ok(hex::encode(state.expect_seed().data()))
     }
 }
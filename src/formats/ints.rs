@@ -17,17 +17,17 @@ impl Format for IntsFormat {
 }
 
 impl InputFormat for IntsFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         let string = state.expect_input()?;
         let entropy = parse_ints(&string)?;
         state.seed =
             Some(Seed::new(deterministic_random(&entropy, state.count)));
-        Ok(state)
+        Ok(())
     }
 }
 
 impl OutputFormat for IntsFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
+    fn process_output(&self, state: &Cli) -> Result<String> {
         data_to_ints(state.expect_seed().data(), state.low, state.high, " ")
     }
 }
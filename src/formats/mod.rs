@@ -3,6 +3,8 @@ pub use format::{
     Format, InputFormat, InputFormatKey, OutputFormat, OutputFormatKey,
     select_input_format, select_output_format,
 };
+mod auto;
+pub use auto::AutoFormat;
 mod base6;
 pub use base6::Base6Format;
 mod base10;
@@ -28,9 +30,14 @@ pub use ints::IntsFormat;
 mod random;
 pub use random::RandomFormat;
 mod sskr;
-pub use sskr::{SSKRFormat, SSKRFormatKey};
+pub use sskr::{
+    DecodedShare, SSKRFormat, SSKRFormatKey, ShareCollector, ShareKeys,
+    decode_shares, describe_policy,
+};
 mod envelope;
-pub use envelope::EnvelopeFormat;
+pub use envelope::{
+    ElidedPart, EnvelopeFormat, PasswordKdf, check_envelope, unwrap_signed,
+};
 mod seed;
 pub use seed::SeedFormat;
 mod multipart;
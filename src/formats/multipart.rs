@@ -1,8 +1,9 @@
 use anyhow::{Result, bail};
 use bc_envelope::Envelope;
 use bc_ur::{MultipartDecoder, MultipartEncoder, URDecodable, UREncodable};
+use serde_json::{Value, json};
 
-use super::{Format, InputFormat, OutputFormat};
+use super::{Format, InputFormat, OutputFormat, envelope::unwrap_signed};
 use crate::{cli::Cli, seed::Seed};
 
 pub struct MultipartFormat;
@@ -14,7 +15,7 @@ impl Format for MultipartFormat {
 }
 
 impl InputFormat for MultipartFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         let input = state.expect_input()?;
         let shares: Vec<&str> = input.split_whitespace().collect();
 
@@ -31,20 +32,61 @@ impl InputFormat for MultipartFormat {
         }
         let ur = decoder.message().unwrap().unwrap();
         let envelope = Envelope::from_ur(&ur)?;
+        let envelope = unwrap_signed(envelope, state.verifying_key.as_ref())?;
         let seed = Seed::try_from(envelope)?;
         state.seed = Some(seed);
-        Ok(state)
+        Ok(())
     }
 }
 
 impl OutputFormat for MultipartFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
-        let ur = state.to_envelope().ur();
-        let mut encoder = MultipartEncoder::new(&ur, state.max_fragment_len)?;
-        let parts_count = encoder.parts_count() + state.additional_parts;
-        let parts = (0..parts_count)
-            .map(|_| encoder.next_part().map_err(anyhow::Error::from))
-            .collect::<Result<Vec<String>>>()?;
+    fn process_output(&self, state: &Cli) -> Result<String> {
+        let parts = make_parts(state)?
+            .into_iter()
+            .map(|(_, _, part)| part)
+            .collect::<Vec<_>>();
         Ok(parts.join("\n"))
     }
+
+    fn process_output_json(&self, state: &Cli) -> Result<Value> {
+        let parts = make_parts(state)?
+            .into_iter()
+            .map(|(seq_num, seq_len, part)| {
+                json!({ "seqNum": seq_num, "seqLen": seq_len, "part": part })
+            })
+            .collect::<Vec<_>>();
+        Ok(json!({ "format": self.name(), "parts": parts }))
+    }
+
+    fn process_output_files(
+        &self,
+        state: &Cli,
+    ) -> Result<Vec<(String, String)>> {
+        Ok(make_parts(state)?
+            .into_iter()
+            .map(|(seq_num, _, part)| {
+                (format!("{}-part-{}.txt", self.name(), seq_num), part)
+            })
+            .collect())
+    }
+}
+
+/// Returns each part along with its sequence number and the number of
+/// fragments in the message, which is the seed envelope, signed if a signing
+/// key is given.
+fn make_parts(state: &Cli) -> Result<Vec<(usize, usize, String)>> {
+    let mut envelope = state.to_envelope();
+    if let Some(signing_key) = &state.signing_key {
+        envelope = envelope.sign(&**signing_key);
+    }
+    let ur = envelope.ur();
+    let mut encoder = MultipartEncoder::new(&ur, state.max_fragment_len)?;
+    let seq_len = encoder.parts_count();
+    let parts_count = seq_len + state.additional_parts;
+    (0..parts_count)
+        .map(|_| {
+            let part = encoder.next_part()?;
+            Ok((encoder.current_index(), seq_len, part))
+        })
+        .collect()
 }
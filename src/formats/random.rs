@@ -12,8 +12,8 @@ impl Format for RandomFormat {
 }
 
 impl InputFormat for RandomFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         state.seed = Some(Seed::new(state.random_data(state.count)));
-        Ok(state)
+        Ok(())
     }
 }
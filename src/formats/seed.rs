@@ -1,10 +1,16 @@
-use anyhow::Result;
-use bc_components::Seed as ComponentsSeed;
+use anyhow::{Result, bail};
+use bc_components::{Seed as ComponentsSeed, tags};
 use bc_ur::prelude::*;
 
 use super::{Format, InputFormat, OutputFormat};
 use crate::{cli::Cli, seed::Seed};
 
+/// The tag for a date as a number of days since the Unix epoch (RFC 8943),
+/// used for the birthdate of a legacy `ur:crypto-seed`.
+const TAG_DAYS_SINCE_EPOCH: u64 = 100;
+
+const SECONDS_PER_DAY: i64 = 86400;
+
 pub struct SeedFormat;
 
 impl Format for SeedFormat {
@@ -14,18 +20,93 @@ impl Format for SeedFormat {
 }
 
 impl InputFormat for SeedFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
         let input = state.expect_input()?;
-        let components_seed = ComponentsSeed::from_ur_string(&input)?;
-        state.seed = Some(Seed::from(components_seed));
-        Ok(state)
+        let ur = UR::from_ur_string(input.as_str())?;
+        let seed = if ur.ur_type_str() == "crypto-seed" {
+            seed_from_legacy_cbor(ur.cbor())?
+        } else {
+            Seed::from(ComponentsSeed::from_ur(&ur)?)
+        };
+        state.seed = Some(seed);
+        Ok(())
     }
 }
 
 impl OutputFormat for SeedFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
-        let seed = state.seed_with_overrides();
+    fn process_output(&self, state: &Cli) -> Result<String> {
+        let seed = state.expect_seed();
+        if state.legacy_ur {
+            return Ok(UR::new("crypto-seed", legacy_seed_cbor(seed))?.string());
+        }
         let components_seed = ComponentsSeed::try_from(seed)?;
         Ok(components_seed.ur_string())
     }
 }
+
+/// The CBOR of a legacy `ur:crypto-seed`, which has the same structure as
+/// `ur:seed` except that its date is a whole number of days.
+fn legacy_seed_cbor(seed: &Seed) -> CBOR {
+    let mut map = Map::new();
+    map.insert(1, CBOR::to_byte_string(seed.data()));
+    if let Some(creation_date) = seed.creation_date() {
+        let days =
+            (creation_date.timestamp() as i64).div_euclid(SECONDS_PER_DAY);
+        map.insert(2, CBOR::to_tagged_value(TAG_DAYS_SINCE_EPOCH, days));
+    }
+    if !seed.name().is_empty() {
+        map.insert(3, seed.name());
+    }
+    if !seed.note().is_empty() {
+        map.insert(4, seed.note());
+    }
+    map.into()
+}
+
+/// Decodes the CBOR of a legacy `ur:crypto-seed`, whose date may be a number
+/// of days or a current date. Some encoders tagged the CBOR, even though it is
+/// a UR.
+fn seed_from_legacy_cbor(cbor: CBOR) -> Result<Seed> {
+    let cbor = cbor
+        .clone()
+        .try_into_expected_tagged_value(tags::TAG_SEED_V1)
+        .unwrap_or(cbor);
+    let mut map = cbor.try_into_map()?;
+    if let Some(date) = map.get::<i32, CBOR>(2)
+        && let Ok(days) =
+            date.try_into_expected_tagged_value(TAG_DAYS_SINCE_EPOCH)
+    {
+        let days: i64 = days.try_into()?;
+        let Some(seconds) = days.checked_mul(SECONDS_PER_DAY) else {
+            bail!("The seed's date is out of range.");
+        };
+        map.insert(2, Date::from_timestamp(seconds as f64));
+    }
+    Ok(Seed::from_untagged_cbor(map.into())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_seed() {
+        let mut seed = Seed::new_opt(
+            hex::decode("9d347f841a4e2ce6bc886e1aee74d824").unwrap(),
+            "Alice",
+            "",
+            Some(Date::from_string("2021-02-24T09:30:00Z").unwrap()),
+        );
+        let cbor = legacy_seed_cbor(&seed);
+        assert_eq!(
+            cbor.diagnostic_flat(),
+            r#"{1: h'9d347f841a4e2ce6bc886e1aee74d824', 2: 100(18682), 3: "Alice"}"#
+        );
+        let decoded = seed_from_legacy_cbor(cbor).unwrap();
+        seed.set_creation_date(Some(Date::from_string("2021-02-24").unwrap()));
+        assert_eq!(decoded, seed);
+
+        // A current seed's CBOR, with a full date, is also accepted.
+        assert_eq!(seed_from_legacy_cbor(seed.untagged_cbor()).unwrap(), seed);
+    }
+}
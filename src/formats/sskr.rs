@@ -1,11 +1,24 @@
-use anyhow::{Result, bail};
-use bc_components::{SSKRShare, SymmetricKey, sskr_generate, tags};
+use anyhow::{Result, anyhow, bail};
+use bc_components::{
+    PrivateKeys, SSKRShare, SigningPrivateKey, SigningPublicKey, SymmetricKey,
+    sskr_generate, tags,
+};
 use bc_envelope::prelude::*;
 use clap::ValueEnum;
+use serde_json::{Value, json};
 use sskr::{Secret, Spec};
+use zeroize::Zeroizing;
 
-use super::{Format, InputFormat, OutputFormat};
-use crate::{cli::Cli, seed::Seed};
+use super::{Format, InputFormat, OutputFormat, unwrap_signed};
+use crate::{
+    cli::Cli,
+    distribution::{check_distribution, write_distribution},
+    prompt::prompt_sskr_seed,
+    seed::Seed,
+    share_manifest::ShareManifest,
+    shares::{ShareProblem, SplitStatus, analyze_shares},
+    util::{binomial, combinations},
+};
 
 pub struct SSKRFormat;
 
@@ -16,10 +29,44 @@ impl Format for SSKRFormat {
 }
 
 impl InputFormat for SSKRFormat {
-    fn process_input(&self, mut state: Cli) -> Result<Cli> {
+    fn process_input(&self, state: &mut Cli) -> Result<()> {
+        if state.prompt {
+            let mut collector = ShareCollector::new(&ShareKeys::new(state));
+            let seed = prompt_sskr_seed(&mut collector)?;
+            if let Some(path) = &state.sskr_distribution_check {
+                check_distribution(
+                    path,
+                    state.sskr_distribution_verifier.as_ref(),
+                    &collector.numbered(),
+                    &seed,
+                )?;
+            }
+            state.seed = Some(seed);
+            state.sskr_input_identifiers = collector.identifiers();
+            return Ok(());
+        }
         let input = state.expect_input()?;
-        state.seed = Some(parse_sskr_seed(&input)?);
-        Ok(state)
+        let keys = ShareKeys::new(state);
+        let seed = parse_sskr_seed(&input, &keys)?;
+        let decoded = decode_shares(&input, &keys);
+        let shares: Vec<(usize, &DecodedShare)> = decoded
+            .iter()
+            .filter_map(|(line, result)| Some((*line, result.as_ref().ok()?)))
+            .collect();
+        if let Some(path) = &state.sskr_distribution_check {
+            check_distribution(
+                path,
+                state.sskr_distribution_verifier.as_ref(),
+                &shares,
+                &seed,
+            )?;
+        }
+        state.seed = Some(seed);
+        state.sskr_input_identifiers = shares
+            .iter()
+            .map(|(_, decoded)| decoded.share.identifier())
+            .collect();
+        Ok(())
     }
 }
 
@@ -32,12 +79,103 @@ pub enum SSKRFormatKey {
     Ur,
 }
 
+/// Options controlling how a seed is split into SSKR shares.
+#[derive(Default)]
+pub struct SplitOptions<'a> {
+    /// Identifiers the new split must not have, so that it can't be confused
+    /// with the split it replaces.
+    pub excluded_identifiers: &'a [u16],
+    /// Whether to verify that the shares implement the spec before
+    /// outputting them.
+    pub verify: bool,
+    /// Whether non-envelope shares carry the seed's metadata.
+    pub metadata: bool,
+    /// Whether `ur` shares use the legacy `ur:crypto-sskr` type.
+    pub legacy_ur: bool,
+    /// Labels to add to each share envelope.
+    pub manifest: Option<ShareManifest>,
+    /// Where to write the distribution manifest of the split.
+    pub distribution: Option<&'a str>,
+    /// The key to sign the distribution manifest with.
+    pub distribution_signer: Option<&'a PrivateKeys>,
+    /// The key to sign each share envelope with.
+    pub signer: Option<&'a SigningPrivateKey>,
+}
+
+impl<'a> SplitOptions<'a> {
+    fn new(state: &'a Cli) -> Result<Self> {
+        let manifest = state
+            .sskr_manifest
+            .as_deref()
+            .map(ShareManifest::load)
+            .transpose()?;
+        Ok(Self {
+            excluded_identifiers: &state.sskr_input_identifiers,
+            verify: state.sskr_verify,
+            metadata: state.sskr_metadata,
+            legacy_ur: state.legacy_ur,
+            manifest,
+            distribution: state.sskr_distribution.as_deref(),
+            distribution_signer: state.sskr_distribution_signer.as_deref(),
+            signer: state.signing_key.as_deref(),
+        })
+    }
+}
+
 impl OutputFormat for SSKRFormat {
-    fn process_output(&self, state: Cli) -> Result<String> {
+    fn process_output(&self, state: &Cli) -> Result<String> {
         let spec = state.sskr_spec()?;
         let seed = state.expect_seed();
         let format = state.sskr_format;
-        output_sskr_seed(seed, &spec, &format)
+        output_sskr_seed(seed, &spec, &format, &SplitOptions::new(state)?)
+    }
+
+    fn process_output_json(&self, state: &Cli) -> Result<Value> {
+        let spec = state.sskr_spec()?;
+        let seed = state.expect_seed();
+        let format = state.sskr_format;
+        let options = SplitOptions::new(state)?;
+        let shares = output_sskr_shares(seed, &spec, &format, &options)?
+            .into_iter()
+            .map(|(share, string)| {
+                json!({
+                    "identifier": share.identifier_hex(),
+                    "groupIndex": share.group_index(),
+                    "groupThreshold": share.group_threshold(),
+                    "groupCount": share.group_count(),
+                    "memberIndex": share.member_index(),
+                    "memberThreshold": share.member_threshold(),
+                    "share": string,
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok(json!({
+            "format": self.name(),
+            "sskrFormat": format.to_possible_value().unwrap().get_name(),
+            "shares": shares,
+        }))
+    }
+
+    fn process_output_files(
+        &self,
+        state: &Cli,
+    ) -> Result<Vec<(String, String)>> {
+        let spec = state.sskr_spec()?;
+        let seed = state.expect_seed();
+        let format = state.sskr_format;
+        let options = SplitOptions::new(state)?;
+        Ok(output_sskr_shares(seed, &spec, &format, &options)?
+            .into_iter()
+            .map(|(share, string)| {
+                let name = format!(
+                    "{}-group-{}-member-{}.txt",
+                    self.name(),
+                    share.group_index() + 1,
+                    share.member_index() + 1
+                );
+                (name, string)
+            })
+            .collect())
     }
 }
 
@@ -49,7 +187,93 @@ fn output_sskr_seed(
     seed: &Seed,
     spec: &Spec,
     format: &SSKRFormatKey,
+    options: &SplitOptions<'_>,
 ) -> Result<String> {
+    let strings = output_sskr_shares(seed, spec, format, options)?
+        .into_iter()
+        .map(|(_, string)| string)
+        .collect::<Vec<_>>();
+    Ok(strings.join("\n"))
+}
+
+/// Splits the seed, returning each share along with its encoding in the
+/// requested format.
+fn output_sskr_shares(
+    seed: &Seed,
+    spec: &Spec,
+    format: &SSKRFormatKey,
+    options: &SplitOptions<'_>,
+) -> Result<Vec<(SSKRShare, String)>> {
+    let manifest = options.manifest.as_ref();
+    if let Some(manifest) = manifest {
+        if *format != SSKRFormatKey::Envelope {
+            bail!("Share manifests require the envelope SSKR format.");
+        }
+        manifest.check(spec)?;
+    }
+    if options.signer.is_some() && *format != SSKRFormatKey::Envelope {
+        bail!("Signing shares requires the envelope SSKR format.");
+    }
+    let shares = loop {
+        let shares = split_seed(seed, spec, format, options)?;
+        if !options
+            .excluded_identifiers
+            .contains(&shares[0].0.identifier())
+        {
+            break shares;
+        }
+    };
+    if options.verify {
+        eprintln!("Policy: {}.", describe_policy(spec));
+        let strings: Vec<&str> =
+            shares.iter().map(|(_, string)| string.as_str()).collect();
+        let (qualifying, non_qualifying) = verify_split(seed, &strings)?;
+        eprintln!(
+            "Verified: each of the {} minimal sets of shares meeting the policy recovers the seed, and none of the {} largest sets falling short of it does.",
+            qualifying, non_qualifying
+        );
+    }
+    // Labelling and signing come after verification, since verification
+    // can't decrypt shares encrypted to a custodian.
+    let shares = if manifest.is_some() || options.signer.is_some() {
+        shares
+            .into_iter()
+            .map(|(share, string)| {
+                let mut envelope = Envelope::from_ur_string(&string)?;
+                if let Some(manifest) = manifest {
+                    let labels = manifest
+                        .labels(share.group_index(), share.member_index());
+                    envelope = labels.apply(envelope);
+                }
+                if let Some(signer) = options.signer {
+                    envelope = envelope.sign(signer);
+                }
+                Ok((share, envelope.ur_string()))
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        shares
+    };
+    if let Some(path) = options.distribution {
+        write_distribution(
+            path,
+            seed,
+            spec,
+            &shares,
+            manifest,
+            options.distribution_signer,
+        )?;
+    }
+    Ok(shares)
+}
+
+fn split_seed(
+    seed: &Seed,
+    spec: &Spec,
+    format: &SSKRFormatKey,
+    options: &SplitOptions<'_>,
+) -> Result<Vec<(SSKRShare, String)>> {
+    let metadata = options.metadata;
     match format {
         SSKRFormatKey::Envelope => {
             let envelope = seed.to_envelope();
@@ -58,38 +282,87 @@ fn output_sskr_seed(
                 envelope.wrap().encrypt_subject(&content_key)?;
             let share_envelopes =
                 encrypted_envelope.sskr_split_flattened(spec, &content_key)?;
-            let share_envelopes_strings = share_envelopes
-                .iter()
-                .map(|envelope| envelope.ur_string())
-                .collect::<Vec<_>>();
-            Ok(share_envelopes_strings.join("\n"))
-        }
-        SSKRFormatKey::Btw => {
-            make_bytewords_shares(spec, seed, bytewords::Style::Standard)
-        }
-        SSKRFormatKey::Btwm => {
-            make_bytewords_shares(spec, seed, bytewords::Style::Minimal)
-        }
-        SSKRFormatKey::Btwu => {
-            make_bytewords_shares(spec, seed, bytewords::Style::Uri)
-        }
-        SSKRFormatKey::Ur => {
-            let shares = make_shares(spec, seed)?
+            share_envelopes
                 .iter()
-                .map(|share| {
-                    UR::new("sskr", CBOR::to_byte_string(share.as_bytes()))
-                        .map(|ur| ur.string())
-                        .map_err(anyhow::Error::from)
+                .map(|envelope| {
+                    let share = envelope
+                        .extract_object_for_predicate::<SSKRShare>(
+                            known_values::SSKR_SHARE,
+                        )?;
+                    Ok((share, envelope.ur_string()))
                 })
-                .collect::<Result<Vec<_>>>()?
-                .join("\n");
-            Ok(shares)
+                .collect()
         }
+        SSKRFormatKey::Btw => make_bytewords_shares(
+            spec,
+            &share_secret(seed, metadata)?,
+            bytewords::Style::Standard,
+        ),
+        SSKRFormatKey::Btwm => make_bytewords_shares(
+            spec,
+            &share_secret(seed, metadata)?,
+            bytewords::Style::Minimal,
+        ),
+        SSKRFormatKey::Btwu => make_bytewords_shares(
+            spec,
+            &share_secret(seed, metadata)?,
+            bytewords::Style::Uri,
+        ),
+        SSKRFormatKey::Ur => make_shares(spec, &share_secret(seed, metadata)?)?
+            .into_iter()
+            .map(|share| {
+                let ur_type = if options.legacy_ur {
+                    "crypto-sskr"
+                } else {
+                    "sskr"
+                };
+                let ur =
+                    UR::new(ur_type, CBOR::to_byte_string(share.as_bytes()))?;
+                Ok((share, ur.string()))
+            })
+            .collect(),
+    }
+}
+
+/// The secret split into non-envelope shares: the seed itself, or with
+/// `metadata` its tagged CBOR, padded with a zero byte to the even length
+/// SSKR requires.
+fn share_secret(seed: &Seed, metadata: bool) -> Result<Zeroizing<Vec<u8>>> {
+    if !metadata {
+        return Ok(Zeroizing::new(seed.data().to_vec()));
     }
+    let mut data = Zeroizing::new(seed.tagged_cbor().to_cbor_data());
+    if !data.len().is_multiple_of(2) {
+        data.push(0);
+    }
+    if data.len() > sskr::MAX_SECRET_LEN {
+        bail!(
+            "The seed and its metadata take {} bytes, but SSKR shares can hold at most {}. Shorten the metadata, or use the envelope SSKR format.",
+            data.len(),
+            sskr::MAX_SECRET_LEN
+        );
+    }
+    Ok(data)
+}
+
+/// The seed recovered from the secret of non-envelope shares. The secret is
+/// read as a seed's tagged CBOR only if it is exactly what `share_secret`
+/// makes of that seed with metadata, padding and all. Any other secret is the
+/// seed itself, even if it starts like tagged seed CBOR.
+fn seed_from_secret(data: &[u8]) -> Seed {
+    let unpadded = data.strip_suffix(&[0]).unwrap_or(data);
+    [data, unpadded]
+        .into_iter()
+        .filter_map(|cbor| Seed::try_from(CBOR::try_from_data(cbor).ok()?).ok())
+        .find(|seed| {
+            share_secret(seed, true)
+                .is_ok_and(|secret| secret.as_slice() == data)
+        })
+        .unwrap_or_else(|| Seed::new(data))
 }
 
-fn make_shares(spec: &sskr::Spec, seed: &Seed) -> Result<Vec<SSKRShare>> {
-    let secret = Secret::new(seed.data())?;
+fn make_shares(spec: &sskr::Spec, secret: &[u8]) -> Result<Vec<SSKRShare>> {
+    let secret = Secret::new(secret)?;
     let shares = sskr_generate(spec, &secret)?
         .into_iter()
         .flatten()
@@ -99,145 +372,572 @@ fn make_shares(spec: &sskr::Spec, seed: &Seed) -> Result<Vec<SSKRShare>> {
 
 fn make_bytewords_shares(
     spec: &sskr::Spec,
-    seed: &Seed,
+    secret: &[u8],
     style: bytewords::Style,
-) -> Result<String> {
-    let shares = make_shares(spec, seed).unwrap();
-    let cbor_shares = shares
-        .iter()
+) -> Result<Vec<(SSKRShare, String)>> {
+    let shares = make_shares(spec, secret)?;
+    let shares_strings = shares
+        .into_iter()
         .map(|share| {
-            CBOR::to_tagged_value(
+            let cbor = CBOR::to_tagged_value(
                 tags::TAG_SSKR_SHARE,
                 CBOR::to_byte_string(share.as_bytes()),
-            )
+            );
+            let string = bytewords::encode(cbor.to_cbor_data(), style);
+            (share, string)
         })
-        .collect::<Vec<_>>();
-    let shares_strings = cbor_shares
-        .iter()
-        .map(|share| bytewords::encode(share.to_cbor_data(), style))
-        .collect::<Vec<_>>();
-    Ok(shares_strings.join("\n"))
+        .collect();
+    Ok(shares_strings)
 }
 
 //
 // Input Helpers
 //
 
-fn parse_envelopes(input: &str) -> Result<Seed> {
-    let share_strings: Vec<String> =
-        input.split_whitespace().map(|s| s.to_string()).collect();
-    let share_envelopes: Vec<Envelope> = share_strings
-        .iter()
-        .filter_map(|string| Envelope::from_ur_string(string).ok())
-        .collect();
-    let share_envelopes_refs: Vec<&Envelope> = share_envelopes.iter().collect();
-    let recovered_envelope =
-        Envelope::sskr_join(&share_envelopes_refs)?.try_unwrap()?;
-    Seed::try_from(recovered_envelope)
+/// How an SSKR share is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShareEncoding {
+    Envelope,
+    Btw,
+    Btwm,
+    Btwu,
+    Ur,
+    LegacyUr,
 }
 
-fn from_untagged_cbor_shares(untagged_cbor_shares: Vec<CBOR>) -> Result<Seed> {
-    let data_shares: Vec<Vec<u8>> = untagged_cbor_shares
-        .into_iter()
-        .map(|cbor| cbor.try_into_byte_string().map_err(anyhow::Error::from))
-        .collect::<Result<Vec<_>>>()?;
-    let recovered_secret: Secret = sskr::sskr_combine(&data_shares)?;
-    Ok(Seed::new(recovered_secret.data()))
+impl ShareEncoding {
+    pub fn name(&self) -> &str {
+        match self {
+            ShareEncoding::Envelope => "envelope",
+            ShareEncoding::Btw => "btw",
+            ShareEncoding::Btwm => "btwm",
+            ShareEncoding::Btwu => "btwu",
+            ShareEncoding::Ur => "ur",
+            ShareEncoding::LegacyUr => "crypto-sskr",
+        }
+    }
 }
 
-fn from_tagged_cbor_shares(tagged_cbor_shares: Vec<CBOR>) -> Result<Seed> {
-    let untagged_cbor_shares: Vec<CBOR> = tagged_cbor_shares
-        .into_iter()
-        .map(|cbor| {
-            cbor.try_into_expected_tagged_value(tags::TAG_SSKR_SHARE)
-                .map_err(anyhow::Error::from)
-        })
-        .collect::<Result<Vec<_>>>()?;
-    from_untagged_cbor_shares(untagged_cbor_shares)
+/// The keys used to open share envelopes.
+#[derive(Default, Clone)]
+pub struct ShareKeys {
+    /// The custodians' private keys, to decrypt shares encrypted to them.
+    pub decryption: Vec<PrivateKeys>,
+    /// The key every share must be signed by, if any.
+    pub verifier: Option<SigningPublicKey>,
 }
 
-fn parse_bytewords(input: &str, style: bytewords::Style) -> Result<Seed> {
-    // Standard bytewords include spaces, so we can only split on newlines.
-    let share_strings: Vec<String> = match style {
-        bytewords::Style::Standard => {
-            input.split('\n').map(|s| s.to_string()).collect()
+impl ShareKeys {
+    pub fn new(state: &Cli) -> Self {
+        Self {
+            decryption: state.sskr_key.iter().map(|k| k.key.clone()).collect(),
+            verifier: state.verifying_key.clone(),
         }
-        _ => input.split_whitespace().map(|s| s.to_string()).collect(),
+    }
+}
+
+/// A single SSKR share decoded from its text encoding.
+pub struct DecodedShare {
+    pub share: SSKRShare,
+    pub encoding: ShareEncoding,
+    /// For `envelope` shares, the envelope carrying the encrypted seed.
+    pub envelope: Option<Envelope>,
+}
+
+/// Decodes a single SSKR share in any of the supported encodings. An envelope
+/// share encrypted to a custodian is decrypted with whichever of `keys` can.
+pub fn decode_share(string: &str, keys: &ShareKeys) -> Result<DecodedShare> {
+    let string = string.trim();
+    let (share, encoding, envelope) = if string
+        .to_lowercase()
+        .starts_with("ur:")
+    {
+        let ur = UR::from_ur_string(string)?;
+        match ur.ur_type_str() {
+            "envelope" => {
+                let mut envelope = unwrap_signed(
+                    Envelope::from_ur(&ur)?,
+                    keys.verifier.as_ref(),
+                )?;
+                if !envelope
+                    .assertions_with_predicate(known_values::HAS_RECIPIENT)
+                    .is_empty()
+                {
+                    envelope = decrypt_share(&envelope, &keys.decryption)?;
+                }
+                let share = envelope
+                    .extract_object_for_predicate::<SSKRShare>(
+                        known_values::SSKR_SHARE,
+                    )
+                    .map_err(|_| anyhow!("Envelope is not an SSKR share."))?;
+                (share, ShareEncoding::Envelope, Some(envelope))
+            }
+            "sskr" => {
+                let data = ur.cbor().try_into_byte_string()?;
+                (SSKRShare::from_data(data), ShareEncoding::Ur, None)
+            }
+            "crypto-sskr" => {
+                // Legacy SSKR shares might have tagged CBOR, even though
+                // they're URs so they shouldn't be.
+                let mut cbor = ur.cbor();
+                if let Ok(untagged_cbor) = cbor
+                    .clone()
+                    .try_into_expected_tagged_value(tags::TAG_SSKR_SHARE_V1)
+                {
+                    cbor = untagged_cbor;
+                }
+                let data = cbor.try_into_byte_string()?;
+                (SSKRShare::from_data(data), ShareEncoding::LegacyUr, None)
+            }
+            ur_type => bail!("Not an SSKR share: ur:{}", ur_type),
+        }
+    } else {
+        let (style, encoding) = if string.contains(' ') {
+            (bytewords::Style::Standard, ShareEncoding::Btw)
+        } else if string.contains('-') {
+            (bytewords::Style::Uri, ShareEncoding::Btwu)
+        } else {
+            (bytewords::Style::Minimal, ShareEncoding::Btwm)
+        };
+        let data = bytewords::decode(string, style)?;
+        let data = CBOR::try_from_data(data)?
+            .try_into_expected_tagged_value(tags::TAG_SSKR_SHARE)?
+            .try_into_byte_string()?;
+        (SSKRShare::from_data(data), encoding, None)
     };
-    let cbor_data_shares: Vec<Vec<u8>> = share_strings
-        .iter()
-        .filter_map(|s| bytewords::decode(s, style).ok())
-        .collect();
-    let tagged_cbor_shares: Vec<CBOR> = cbor_data_shares
-        .into_iter()
-        .map(|data| CBOR::try_from_data(data).map_err(anyhow::Error::from))
-        .collect::<Result<Vec<_>>>()?;
-    from_tagged_cbor_shares(tagged_cbor_shares)
+    if keys.verifier.is_some() && encoding != ShareEncoding::Envelope {
+        bail!("Share is not an envelope, so it can't be signed.");
+    }
+    check_share(&share)?;
+    Ok(DecodedShare {
+        share,
+        encoding,
+        envelope,
+    })
 }
 
-fn parse_ur(
+/// Decrypts a share envelope encrypted to a custodian's public key. Any labels
+/// outside the encryption are discarded.
+fn decrypt_share(
+    envelope: &Envelope,
+    keys: &[PrivateKeys],
+) -> Result<Envelope> {
+    if keys.is_empty() {
+        bail!(
+            "Share is encrypted to a custodian. Use --sskr-key to decrypt it."
+        );
+    }
+    keys.iter()
+        .find_map(|key| {
+            envelope
+                .decrypt_subject_to_recipient(key)
+                .ok()?
+                .subject()
+                .try_unwrap()
+                .ok()
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Share is encrypted to a custodian, but none of the keys given decrypt it."
+            )
+        })
+}
+
+/// Checks that the share's metadata is self-consistent, and that it carries
+/// a share of a 16 to 32 byte secret.
+fn check_share(share: &SSKRShare) -> Result<()> {
+    let len = share.as_bytes().len();
+    if !(5 + 16..=5 + 32).contains(&len) || len.is_multiple_of(2) {
+        bail!("Share has an invalid length.");
+    }
+    if share.group_threshold() > share.group_count()
+        || share.group_index() >= share.group_count()
+        || share.as_bytes()[4] >> 4 != 0
+    {
+        bail!("Share has invalid metadata.");
+    }
+    Ok(())
+}
+
+/// Decodes every share in the input, pairing each with its line number.
+///
+/// Bytewords Standard shares contain spaces, so a line is first tried as a
+/// single share. Otherwise each word on the line is tried separately, and if
+/// none of them is a share the line is reported once.
+pub fn decode_shares(
     input: &str,
-    expected_tag_value: TagValue,
-    allow_tagged_cbor: bool,
-) -> Result<Seed> {
-    let expected_tag = with_tags!(|tags: &TagsStore| {
-        tags.tag_for_value(expected_tag_value).unwrap()
-    });
-    let share_strings: Vec<String> =
-        input.split_whitespace().map(|s| s.to_string()).collect();
-    let urs: Vec<UR> = share_strings
+    keys: &ShareKeys,
+) -> Vec<(usize, Result<DecodedShare>)> {
+    let mut results = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let whole_line = decode_share(line, keys);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if whole_line.is_ok() || words.len() == 1 {
+            results.push((i + 1, whole_line));
+            continue;
+        }
+        let shares: Vec<Result<DecodedShare>> =
+            words.iter().map(|word| decode_share(word, keys)).collect();
+        if shares.iter().any(Result::is_ok) {
+            results.extend(shares.into_iter().map(|share| (i + 1, share)));
+        } else {
+            results.push((i + 1, whole_line));
+        }
+    }
+    results
+}
+
+/// Recovers the seed from the shares in the input, ignoring anything that
+/// isn't a share. If the seed can't be recovered, the error explains why.
+fn parse_sskr_seed(input: &str, keys: &ShareKeys) -> Result<Seed> {
+    let decoded = decode_shares(input, keys);
+    let shares: Vec<(usize, &DecodedShare)> = decoded
         .iter()
-        .filter_map(|string| UR::from_ur_string(string).ok())
+        .filter_map(|(line, result)| Some((*line, result.as_ref().ok()?)))
         .collect();
-    // ensure every UR is of the expected type
-    for ur in &urs {
-        ur.check_type(expected_tag.name().unwrap())?;
+    let splits = analyze_shares(&shares);
+    if let Some(split) = splits.iter().find(|split| split.is_recoverable()) {
+        let split_shares: Vec<&DecodedShare> =
+            split.usable.iter().map(|p| shares[*p].1).collect();
+        return combine_shares(&split_shares).map_err(|e| {
+            anyhow!(
+                "The shares of split {} could not be combined, so at least one of them is corrupted: {}",
+                split.identifier_hex(),
+                e
+            )
+        });
     }
-    let untagged_cbor_shares: Vec<CBOR> = urs
-        .into_iter()
-        .map(|ur| {
-            // Legacy SSKR shares might have tagged CBOR, even though they're
-            // URs so they shouldn't be.
-            let mut cbor = ur.cbor();
-            if allow_tagged_cbor
-                && let Ok(untagged_cbor) = cbor
-                    .clone()
-                    .try_into_expected_tagged_value(expected_tag.clone())
-            {
-                cbor = untagged_cbor;
+
+    let mut report =
+        vec!["Insufficient SSKR shares to recover the seed.".to_string()];
+    for (line, result) in &decoded {
+        if let Err(error) = result {
+            report.push(format!("Line {} ignored: {}", line, error));
+        }
+    }
+    if splits.is_empty() {
+        report.push("No SSKR shares found.".to_string());
+    }
+    if splits.len() > 1 {
+        report.push(format!(
+            "The shares come from {} different splits, which can't be combined with each other.",
+            splits.len()
+        ));
+    }
+    for split in &splits {
+        report.extend(split.diagnose());
+    }
+    bail!("{}", report.join("\n"));
+}
+
+/// Collects shares as they arrive one at a time, checking each against those
+/// collected so far, until there are enough to recover the seed.
+pub struct ShareCollector {
+    shares: Vec<DecodedShare>,
+    keys: ShareKeys,
+}
+
+impl ShareCollector {
+    pub fn new(keys: &ShareKeys) -> Self {
+        Self {
+            shares: Vec::new(),
+            keys: keys.clone(),
+        }
+    }
+
+    pub fn len(&self) -> usize { self.shares.len() }
+
+    /// Adds a share, returning the seed if it is now recoverable. Fails
+    /// without adding the share if it isn't valid, or can't be combined with
+    /// the shares already collected. Shares are numbered from 1 in the order
+    /// they were added.
+    pub fn add(&mut self, string: &str) -> Result<Option<Seed>> {
+        let decoded = decode_share(string, &self.keys)?;
+        let n = self.shares.len() + 1;
+        let mut shares = self.numbered();
+        shares.push((n, &decoded));
+        let splits = analyze_shares(&shares);
+        if splits.len() > 1 {
+            bail!(
+                "This share is from split {}, but the shares so far are from split {}.",
+                splits[1].identifier_hex(),
+                splits[0].identifier_hex()
+            );
+        }
+        if let Some(problem) = splits[0].problems.first() {
+            match problem {
+                ShareProblem::Duplicate { of_line, .. } => {
+                    bail!("This share duplicates share {}.", of_line)
+                }
+                ShareProblem::Conflict { with_line, .. } => bail!(
+                    "This share is the same member as share {} but differs from it, so one of them is corrupted.",
+                    with_line
+                ),
+                ShareProblem::Inconsistent { .. } => bail!(
+                    "This share's thresholds disagree with those of the shares so far, so it may be corrupted."
+                ),
             }
-            Ok(cbor)
-        })
-        .collect::<Result<Vec<_>>>()?;
-    from_untagged_cbor_shares(untagged_cbor_shares)
+        }
+        if !splits[0].is_recoverable() {
+            self.shares.push(decoded);
+            return Ok(None);
+        }
+        let mut shares: Vec<&DecodedShare> = self.shares.iter().collect();
+        shares.push(&decoded);
+        let seed = combine_shares(&shares).map_err(|e| {
+            anyhow!(
+                "The shares could not be combined, so at least one of them is corrupted: {}",
+                e
+            )
+        })?;
+        self.shares.push(decoded);
+        Ok(Some(seed))
+    }
+
+    /// The identifiers of the splits the shares came from.
+    pub fn identifiers(&self) -> Vec<u16> {
+        self.shares.iter().map(|d| d.share.identifier()).collect()
+    }
+
+    /// Describes the progress toward the group and member thresholds.
+    pub fn progress(&self) -> String {
+        analyze_shares(&self.numbered())
+            .first()
+            .map(SplitStatus::describe)
+            .unwrap_or_default()
+    }
+
+    /// The shares collected, each paired with its number.
+    pub fn numbered(&self) -> Vec<(usize, &DecodedShare)> {
+        self.shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| (i + 1, share))
+            .collect()
+    }
 }
 
-fn parse_sskr_seed(input: &str) -> Result<Seed> {
-    if let Ok(seed) = parse_envelopes(input) {
-        return Ok(seed);
+/// Combines shares known to be from the same split and sufficient to
+/// recover the seed.
+fn combine_shares(shares: &[&DecodedShare]) -> Result<Seed> {
+    let envelopes: Vec<&Envelope> =
+        shares.iter().filter_map(|s| s.envelope.as_ref()).collect();
+    if envelopes.is_empty() {
+        let data_shares: Vec<Vec<u8>> =
+            shares.iter().map(|s| s.share.as_bytes().to_vec()).collect();
+        let recovered_secret: Secret = sskr::sskr_combine(&data_shares)?;
+        return Ok(seed_from_secret(recovered_secret.data()));
     }
+    if envelopes.len() != shares.len() {
+        bail!(
+            "Envelope shares can't be combined with shares in other formats."
+        );
+    }
+    let recovered_envelope = Envelope::sskr_join(&envelopes)?.try_unwrap()?;
+    Seed::try_from(recovered_envelope)
+}
 
-    if let Ok(seed) = parse_bytewords(input, bytewords::Style::Standard) {
-        return Ok(seed);
+//
+// Verification
+//
+
+/// The most sets of shares `--sskr-verify` will test.
+const MAX_VERIFIED_SETS: u128 = 100_000;
+
+fn join_list(items: &[String], conjunction: &str) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [first, second] => format!("{} {} {}", first, conjunction, second),
+        [rest @ .., last] => {
+            format!("{}, {} {}", rest.join(", "), conjunction, last)
+        }
     }
+}
 
-    if let Ok(seed) = parse_bytewords(input, bytewords::Style::Minimal) {
-        return Ok(seed);
+/// Describes in plain language which sets of shares recover the seed.
+pub fn describe_policy(spec: &Spec) -> String {
+    let groups = spec.groups();
+    let members = |group: &sskr::GroupSpec| {
+        format!("{} of {}", group.member_threshold(), group.member_count())
+    };
+    if groups.len() == 1 {
+        return format!("any {} shares", members(&groups[0]));
+    }
+    let parts: Vec<String> = groups
+        .iter()
+        .enumerate()
+        .map(|(i, group)| format!("{} from group {}", members(group), i + 1))
+        .collect();
+    if spec.group_threshold() == groups.len() {
+        format!("any {}", join_list(&parts, "and"))
+    } else {
+        format!(
+            "any {} of the {} groups, with any {}",
+            spec.group_threshold(),
+            groups.len(),
+            join_list(&parts, "or")
+        )
     }
+}
 
-    if let Ok(seed) = parse_bytewords(input, bytewords::Style::Uri) {
-        return Ok(seed);
+/// Calls `f` with each set formed by taking one choice from every element of
+/// `choices`.
+fn for_each_product(
+    choices: &[Vec<Vec<usize>>],
+    f: &mut impl FnMut(&[usize]) -> Result<()>,
+) -> Result<()> {
+    fn product(
+        choices: &[Vec<Vec<usize>>],
+        set: &mut Vec<usize>,
+        f: &mut impl FnMut(&[usize]) -> Result<()>,
+    ) -> Result<()> {
+        let Some((first, rest)) = choices.split_first() else {
+            return f(set);
+        };
+        for choice in first {
+            let len = set.len();
+            set.extend(choice);
+            product(rest, set, f)?;
+            set.truncate(len);
+        }
+        Ok(())
     }
+    product(choices, &mut Vec::new(), f)
+}
 
-    if let Ok(seed) = parse_ur(input, tags::TAG_SSKR_SHARE, false) {
-        return Ok(seed);
+/// Checks that every minimal set of shares meeting the split's policy
+/// recovers the seed, and that no maximal set falling short of it does,
+/// returning the number of each tested. Since SSKR is monotonic, this covers
+/// every subset of the shares.
+fn verify_split(seed: &Seed, strings: &[&str]) -> Result<(usize, usize)> {
+    let shares = strings
+        .iter()
+        .enumerate()
+        .map(|(i, string)| {
+            decode_share(string, &ShareKeys::default())
+                .map_err(|e| anyhow!("Share {} can't be decoded: {}", i + 1, e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let first = &shares[0].share;
+    let group_threshold = first.group_threshold();
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); first.group_count()];
+    let mut member_thresholds = vec![0; first.group_count()];
+    for (position, decoded) in shares.iter().enumerate() {
+        groups[decoded.share.group_index()].push(position);
+        member_thresholds[decoded.share.group_index()] =
+            decoded.share.member_threshold();
     }
+    // Envelope shares also carry the seed's metadata.
+    let has_metadata = shares[0].envelope.is_some();
+    let recovers = |set: &[usize]| {
+        let set: Vec<&DecodedShare> = set.iter().map(|p| &shares[*p]).collect();
+        match combine_shares(&set) {
+            Ok(recovered) if has_metadata => recovered == *seed,
+            Ok(recovered) => recovered.data() == seed.data(),
+            Err(_) => false,
+        }
+    };
 
-    if let Ok(seed) = parse_ur(input, tags::TAG_SSKR_SHARE_V1, true) {
-        return Ok(seed);
+    // A minimal qualifying set has exactly the member threshold of shares
+    // from exactly the group threshold of groups. A maximal non-qualifying
+    // set has every share of one fewer groups, and one fewer than the member
+    // threshold of the others.
+    let member_sets = |group: usize, count: usize| -> Vec<Vec<usize>> {
+        combinations(groups[group].len(), count)
+            .into_iter()
+            .map(|c| c.into_iter().map(|i| groups[group][i]).collect())
+            .collect()
+    };
+    let group_count = groups.len();
+    let qualifying_groups = combinations(group_count, group_threshold);
+    let non_qualifying_groups = combinations(group_count, group_threshold - 1);
+
+    // Count the sets before building any, since a large policy has far too
+    // many to hold in memory.
+    let total = qualifying_groups
+        .iter()
+        .map(|chosen| {
+            chosen
+                .iter()
+                .map(|g| binomial(groups[*g].len(), member_thresholds[*g]))
+                .fold(1u128, u128::saturating_mul)
+        })
+        .chain(non_qualifying_groups.iter().map(|full| {
+            (0..group_count)
+                .filter(|g| !full.contains(g))
+                .map(|g| binomial(groups[g].len(), member_thresholds[g] - 1))
+                .fold(1u128, u128::saturating_mul)
+        }))
+        .fold(0u128, u128::saturating_add);
+    if total > MAX_VERIFIED_SETS {
+        bail!(
+            "The policy has too many sets of shares ({}) to verify exhaustively.",
+            total
+        );
     }
 
-    bail!("Insufficient or invalid SSKR shares.");
+    let qualifying: Vec<Vec<Vec<Vec<usize>>>> = qualifying_groups
+        .into_iter()
+        .map(|chosen| {
+            chosen
+                .into_iter()
+                .map(|g| member_sets(g, member_thresholds[g]))
+                .collect()
+        })
+        .collect();
+    let non_qualifying: Vec<Vec<Vec<Vec<usize>>>> = non_qualifying_groups
+        .into_iter()
+        .map(|full| {
+            (0..group_count)
+                .map(|g| {
+                    if full.contains(&g) {
+                        vec![groups[g].clone()]
+                    } else {
+                        member_sets(g, member_thresholds[g] - 1)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let describe = |set: &[usize]| {
+        set.iter()
+            .map(|p| (p + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut qualifying_count = 0;
+    for choices in &qualifying {
+        for_each_product(choices, &mut |set| {
+            if !recovers(set) {
+                bail!(
+                    "Verification failed: shares {} meet the policy but don't recover the seed.",
+                    describe(set)
+                );
+            }
+            qualifying_count += 1;
+            Ok(())
+        })?;
+    }
+    let mut non_qualifying_count = 0;
+    for choices in &non_qualifying {
+        for_each_product(choices, &mut |set| {
+            if !set.is_empty() && recovers(set) {
+                bail!(
+                    "Verification failed: shares {} fall short of the policy but recover the seed.",
+                    describe(set)
+                );
+            }
+            non_qualifying_count += 1;
+            Ok(())
+        })?;
+    }
+    Ok((qualifying_count, non_qualifying_count))
 }
 
 #[cfg(test)]
@@ -263,7 +963,9 @@ mod tests {
         )
         .unwrap();
 
-        let output = output_sskr_seed(&seed, &spec, format).unwrap();
+        let output =
+            output_sskr_seed(&seed, &spec, format, &SplitOptions::default())
+                .unwrap();
         let share_strings = output
             .split('\n')
             .map(|s| s.to_string())
@@ -282,7 +984,8 @@ mod tests {
             .collect::<Vec<_>>();
         let input = selected_share_strings.join("\n");
         println!("Input: {}", input);
-        let recovered_seed = parse_sskr_seed(&input).unwrap();
+        let recovered_seed =
+            parse_sskr_seed(&input, &ShareKeys::default()).unwrap();
         if check_metadata {
             assert_eq!(recovered_seed, seed);
         } else {
@@ -292,6 +995,8 @@ mod tests {
 
     #[test]
     fn test_formats() {
+        bc_envelope::register_tags();
+
         test_format(&SSKRFormatKey::Envelope, true);
         test_format(&SSKRFormatKey::Btw, false);
         test_format(&SSKRFormatKey::Btwm, false);
@@ -299,6 +1004,96 @@ mod tests {
         test_format(&SSKRFormatKey::Ur, false);
     }
 
+    #[test]
+    fn test_metadata() {
+        bc_envelope::register_tags();
+
+        let seed = Seed::new_opt(
+            hex!("9d347f841a4e2ce6bc886e1aee74d824"),
+            "SeedName",
+            "",
+            None,
+        );
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let options = SplitOptions {
+            metadata: true,
+            ..Default::default()
+        };
+        for format in [
+            SSKRFormatKey::Btw,
+            SSKRFormatKey::Btwm,
+            SSKRFormatKey::Btwu,
+            SSKRFormatKey::Ur,
+        ] {
+            let output =
+                output_sskr_seed(&seed, &spec, &format, &options).unwrap();
+            let input = output.lines().take(2).collect::<Vec<_>>().join("\n");
+            assert_eq!(
+                parse_sskr_seed(&input, &ShareKeys::default()).unwrap(),
+                seed
+            );
+        }
+
+        // Without metadata, the seed alone is split and recovered.
+        let output = output_sskr_seed(
+            &seed,
+            &spec,
+            &SSKRFormatKey::Btwm,
+            &SplitOptions::default(),
+        )
+        .unwrap();
+        let input = output.lines().take(2).collect::<Vec<_>>().join("\n");
+        let recovered = parse_sskr_seed(&input, &ShareKeys::default()).unwrap();
+        assert_eq!(recovered.data(), seed.data());
+        assert_eq!(recovered.name(), "");
+
+        let seed = Seed::new_opt(
+            hex!("9d347f841a4e2ce6bc886e1aee74d824"),
+            "SeedName",
+            "This is the note.",
+            None,
+        );
+        let error =
+            output_sskr_seed(&seed, &spec, &SSKRFormatKey::Btwm, &options)
+                .unwrap_err()
+                .to_string();
+        assert!(error.starts_with("The seed and its metadata take 52 bytes"));
+    }
+
+    #[test]
+    fn test_plain_seed_like_metadata() {
+        bc_envelope::register_tags();
+
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let cbor = Seed::new(hex!("9d347f841a4e2ce6bc886e1aee74d824"))
+            .tagged_cbor()
+            .to_cbor_data();
+        assert!(cbor.starts_with(&hex!("d99d6c")));
+
+        // Plain seeds that start with a whole tagged seed, but aren't exactly
+        // one padded as with `--sskr-metadata`, are recovered as they are.
+        let mut zero_filled = cbor.clone();
+        zero_filled.resize(32, 0);
+        let mut extra_byte = cbor.clone();
+        extra_byte.extend([0, 0]);
+        let mut truncated = cbor.clone();
+        truncated.truncate(16);
+        for data in [zero_filled, extra_byte, truncated] {
+            let seed = Seed::new(data);
+            let output = output_sskr_seed(
+                &seed,
+                &spec,
+                &SSKRFormatKey::Btwm,
+                &SplitOptions::default(),
+            )
+            .unwrap();
+            let input = output.lines().take(2).collect::<Vec<_>>().join("\n");
+            let recovered =
+                parse_sskr_seed(&input, &ShareKeys::default()).unwrap();
+            assert_eq!(recovered.data(), seed.data());
+        }
+    }
+
     #[test]
     fn test_legacy() {
         bc_envelope::register_tags();
@@ -311,7 +1106,7 @@ mod tests {
             ur:crypto-sskr/taadecgomymwbybgaoswleqddlidjnehclnbdaaawdvsosiachtbihzees
             ur:crypto-sskr/taadecgomymwbybgaaeconwemnhhcmeotivdpdftknsptyltjntamtmtvs
         ").trim();
-        let seed = parse_sskr_seed(input).unwrap();
+        let seed = parse_sskr_seed(input, &ShareKeys::default()).unwrap();
         assert_eq!(
             seed.data().to_vec(),
             hex!("9d347f841a4e2ce6bc886e1aee74d824")
@@ -329,13 +1124,206 @@ mod tests {
 
             ur:envelope/lftansfwlrhdcebzgtdmuoasfwjnnyiocfwtiorsrnyazeathtsowloxdsamiagssffxvlgsfrbbhelbetvtlowntksgahrygdkissoygsgypkkgrfvlcllofrlantrdwnhddatansfphdcxlultemsglryauraaesnblndnfglbihmsehtbfsehlsroptkgswdyvdpkmyhpwynnoyamtpsotantkphddazslpadadadkndebdkifwghutmseolfbagltdkodyuevofwbncxhsbegltiskzowljzlkfzuotertatahwk
         ");
-        let seed = parse_sskr_seed(input).unwrap();
+        let seed = parse_sskr_seed(input, &ShareKeys::default()).unwrap();
         assert_eq!(
             seed.data().to_vec(),
             hex!("59f2293a5bce7d4de59e71b4207ac5d2")
         );
     }
 
+    #[test]
+    fn test_verify() {
+        bc_envelope::register_tags();
+
+        let seed = Seed::new(hex!("9d347f841a4e2ce6bc886e1aee74d824"));
+        let spec = Spec::new(
+            2,
+            vec![
+                GroupSpec::new(2, 3).unwrap(),
+                GroupSpec::new(3, 5).unwrap(),
+                GroupSpec::new(1, 1).unwrap(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            describe_policy(&spec),
+            "any 2 of the 3 groups, with any 2 of 3 from group 1, 3 of 5 from group 2, or 1 of 1 from group 3"
+        );
+        for format in [SSKRFormatKey::Envelope, SSKRFormatKey::Btwm] {
+            let output = output_sskr_seed(
+                &seed,
+                &spec,
+                &format,
+                &SplitOptions::default(),
+            )
+            .unwrap();
+            let strings: Vec<&str> = output.lines().collect();
+            // 3 * 10 + 3 * 1 + 10 * 1 qualifying, and 10 * 1 + 3 * 1 + 3 *
+            // 10 non-qualifying.
+            assert_eq!(verify_split(&seed, &strings).unwrap(), (43, 43));
+        }
+
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        assert_eq!(describe_policy(&spec), "any 2 of 3 shares");
+        let spec = Spec::new(
+            2,
+            vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()],
+        )
+        .unwrap();
+        assert_eq!(
+            describe_policy(&spec),
+            "any 2 of 3 from group 1 and 3 of 5 from group 2"
+        );
+    }
+
+    #[test]
+    fn test_share_collector() {
+        bc_envelope::register_tags();
+
+        let seed = Seed::new(hex!("9d347f841a4e2ce6bc886e1aee74d824"));
+        let spec = Spec::new(
+            2,
+            vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()],
+        )
+        .unwrap();
+        let shares = output_sskr_seed(
+            &seed,
+            &spec,
+            &SSKRFormatKey::Envelope,
+            &SplitOptions::default(),
+        )
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+        let other = output_sskr_seed(
+            &seed,
+            &spec,
+            &SSKRFormatKey::Envelope,
+            &SplitOptions::default(),
+        )
+        .unwrap();
+
+        let mut collector = ShareCollector::new(&ShareKeys::default());
+        assert!(collector.add("not a share").is_err());
+        assert!(collector.add(&shares[0]).unwrap().is_none());
+        let error = collector.add(&shares[0]).unwrap_err().to_string();
+        assert_eq!(error, "This share duplicates share 1.");
+        assert!(collector.add(other.lines().next().unwrap()).is_err());
+        assert!(collector.add(&shares[2]).unwrap().is_none());
+        assert!(collector.add(&shares[3]).unwrap().is_none());
+        assert!(collector.add(&shares[4]).unwrap().is_none());
+        assert_eq!(collector.len(), 4);
+        assert!(
+            collector
+                .progress()
+                .contains("Group 2: members 1, 2 present, 3 required")
+        );
+        let recovered = collector.add(&shares[7]).unwrap().unwrap();
+        assert_eq!(recovered, seed);
+    }
+
+    #[test]
+    fn test_share_collector_corrupted() {
+        bc_envelope::register_tags();
+
+        let seed = Seed::new(hex!("9d347f841a4e2ce6bc886e1aee74d824"));
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        let shares = output_sskr_seed(
+            &seed,
+            &spec,
+            &SSKRFormatKey::Btwm,
+            &SplitOptions::default(),
+        )
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+        let mut data =
+            bytewords::decode(&shares[1], bytewords::Style::Minimal).unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        let corrupted = bytewords::encode(&data, bytewords::Style::Minimal);
+
+        // The corrupted share is rejected, and the collector carries on
+        // without it.
+        let mut collector = ShareCollector::new(&ShareKeys::default());
+        assert!(collector.add(&shares[0]).unwrap().is_none());
+        assert!(collector.add(&corrupted).is_err());
+        assert_eq!(collector.len(), 1);
+        let recovered = collector.add(&shares[2]).unwrap().unwrap();
+        assert_eq!(recovered, seed);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        bc_envelope::register_tags();
+
+        let seed = Seed::new(hex!("9d347f841a4e2ce6bc886e1aee74d824"));
+        let spec = Spec::new(
+            2,
+            vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()],
+        )
+        .unwrap();
+        let shares = output_sskr_seed(
+            &seed,
+            &spec,
+            &SSKRFormatKey::Ur,
+            &SplitOptions::default(),
+        )
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+        let other = output_sskr_seed(
+            &seed,
+            &spec,
+            &SSKRFormatKey::Ur,
+            &SplitOptions::default(),
+        )
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+        // Change a character of the share, so its checksum fails.
+        let mut corrupted = shares[4].clone();
+        let last = corrupted.pop().unwrap();
+        corrupted.push(if last == 'a' { 'b' } else { 'a' });
+
+        let input = [
+            shares[0].as_str(),
+            "a comment",
+            shares[1].as_str(),
+            shares[3].as_str(),
+            corrupted.as_str(),
+            other[0].as_str(),
+        ]
+        .join("\n");
+        let error = parse_sskr_seed(&input, &ShareKeys::default())
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Insufficient SSKR shares"));
+        assert!(error.contains("Line 2 ignored"));
+        assert!(error.contains("Line 5 ignored"));
+        assert!(error.contains("2 different splits"));
+        assert!(error.contains("group 1 is satisfied"));
+        assert!(error.contains("need 2 more shares from group 2"));
+
+        let input = [
+            shares[0].as_str(),
+            shares[1].as_str(),
+            shares[3].as_str(),
+            shares[4].as_str(),
+            shares[5].as_str(),
+        ]
+        .join("\n");
+        assert_eq!(
+            parse_sskr_seed(&input, &ShareKeys::default())
+                .unwrap()
+                .data(),
+            seed.data()
+        );
+    }
+
     /// Test fix for [#6](https://github.com/BlockchainCommons/seedtool-cli-rust/issues/6).
     #[test]
     fn test_more_than_enough_envelopes_1() {
@@ -349,7 +1337,7 @@ mod tests {
             from group 2 (insufficient)
             ur:envelope/lftansfwlrhdcebzgtdmuoasfwjnnyiocfwtiorsrnyazeathtsowloxdsamiagssffxvlgsfrbbhelbetvtlowntksgahrygdkissoygsgypkkgrfvlcllofrlantrdwnhddatansfphdcxlultemsglryauraaesnblndnfglbihmsehtbfsehlsroptkgswdyvdpkmyhpwynnoyamtpsotantkphddazslpadbyaedsclwmaocaaemozodmrhgtrycndtspskmyiyrkfeiadkostikepfsekgkklgdlfgsbbtzswk
         ");
-        let seed = parse_sskr_seed(input).unwrap();
+        let seed = parse_sskr_seed(input, &ShareKeys::default()).unwrap();
         assert_eq!(
             seed.data().to_vec(),
             hex!("59f2293a5bce7d4de59e71b4207ac5d2")
@@ -369,7 +1357,7 @@ mod tests {
             ur:envelope/lftansfwlrhdcebzgtdmuoasfwjnnyiocfwtiorsrnyazeathtsowloxdsamiagssffxvlgsfrbbhelbetvtlowntksgahrygdkissoygsgypkkgrfvlcllofrlantrdwnhddatansfphdcxlultemsglryauraaesnblndnfglbihmsehtbfsehlsroptkgswdyvdpkmyhpwynnoyamtpsotantkphddazslpadadaeayjpeefensrfbznsnnswzswtynsaurbaiewmnesfwlvefhwylksrhfjpnectjzhdgturmkfr
             ur:envelope/lftansfwlrhdcebzgtdmuoasfwjnnyiocfwtiorsrnyazeathtsowloxdsamiagssffxvlgsfrbbhelbetvtlowntksgahrygdkissoygsgypkkgrfvlcllofrlantrdwnhddatansfphdcxlultemsglryauraaesnblndnfglbihmsehtbfsehlsroptkgswdyvdpkmyhpwynnoyamtpsotantkphddazslpadadadkndebdkifwghutmseolfbagltdkodyuevofwbncxhsbegltiskzowljzlkfzuotertatahwk
         ");
-        let seed = parse_sskr_seed(input).unwrap();
+        let seed = parse_sskr_seed(input, &ShareKeys::default()).unwrap();
         assert_eq!(
             seed.data().to_vec(),
             hex!("59f2293a5bce7d4de59e71b4207ac5d2")
@@ -1,17 +1,39 @@
 //! A tool for generating and transforming cryptographic seeds.
 #![warn(rust_2018_idioms)]
 
+#[doc(hidden)]
+mod batch;
+#[doc(hidden)]
+mod bip32;
 #[doc(hidden)]
 mod cli;
 #[doc(hidden)]
+mod distribution;
+#[doc(hidden)]
 mod exec;
 #[doc(hidden)]
+mod files;
+#[doc(hidden)]
 mod formats;
 #[doc(hidden)]
+mod mnemonic;
+#[doc(hidden)]
+mod output;
+#[doc(hidden)]
+mod prompt;
+#[doc(hidden)]
 mod random;
 #[doc(hidden)]
+mod recover;
+#[doc(hidden)]
+mod secure;
+#[doc(hidden)]
 mod seed;
 #[doc(hidden)]
+mod share_manifest;
+#[doc(hidden)]
+mod shares;
+#[doc(hidden)]
 mod styles;
 #[doc(hidden)]
 mod util;
@@ -20,11 +42,18 @@ use anyhow::{Result, bail};
 use bc_rand::SecureRandomNumberGenerator;
 use clap::Parser;
 use cli::{Cli, RngSource};
-use formats::{select_input_format, select_output_format};
+use formats::{
+    InputFormatKey, OutputFormatKey, SSKRFormatKey, check_envelope,
+    select_input_format, select_output_format,
+};
+use output::{json_output, text_output};
 use random::DeterministicRandomNumberGenerator;
+use share_manifest::ShareManifest;
+use zeroize::Zeroizing;
 
 #[doc(hidden)]
 fn main() -> Result<()> {
+    secure::disable_core_dumps();
     bc_envelope::register_tags();
 
     let mut cli = Cli::parse();
@@ -38,19 +67,119 @@ fn main() -> Result<()> {
         cli.rng = Some(RngSource::Secure(SecureRandomNumberGenerator));
     }
 
+    if cli.input.is_some() && cli.r#in != InputFormatKey::Random {
+        eprintln!(
+            "Warning: The input was given on the command line, where it may be saved in shell history and seen by other users. Use --prompt, or pipe it to stdin, instead."
+        );
+    }
+    if cli.has_inline_private_key() {
+        eprintln!(
+            "Warning: A private key was given on the command line, where it may be saved in shell history and seen by other users. Give the path of a file containing it instead."
+        );
+    }
+    if let Some(Some(_)) = &cli.passphrase {
+        eprintln!(
+            "Warning: The passphrase was given on the command line, where it may be saved in shell history and seen by other users."
+        );
+    }
+
+    if cli.sskr_reshare {
+        if cli.r#in != InputFormatKey::Sskr {
+            bail!("Input for --sskr-reshare must be sskr.");
+        }
+        cli.out = vec![OutputFormatKey::Sskr];
+    }
+
     let input_format = select_input_format(cli.r#in);
-    let output_format = select_output_format(cli.out);
+    let mut output_keys = Vec::new();
+    for key in &cli.out {
+        if !output_keys.contains(key) {
+            output_keys.push(*key);
+        }
+    }
+    let output_formats: Vec<_> =
+        output_keys.into_iter().map(select_output_format).collect();
 
-    if !output_format.round_trippable() && input_format.name() != "random" {
+    for output_format in &output_formats {
+        if !output_format.round_trippable() && input_format.name() != "random" {
+            bail!(
+                "Input for output form \"{}\" must be random.",
+                output_format.name()
+            );
+        }
+    }
+
+    if cli.envelope_password
+        && cli.out.iter().any(|key| *key != OutputFormatKey::Envelope)
+    {
+        bail!("Output for --envelope-password must be envelope.");
+    }
+    if (!cli.elide.is_empty() || cli.envelope_salt)
+        && cli.out.iter().any(|key| *key != OutputFormatKey::Envelope)
+    {
+        bail!("Output for --elide and --envelope-salt must be envelope.");
+    }
+    let sskr_output = cli.out.contains(&OutputFormatKey::Sskr);
+    if cli.legacy_ur
+        && (cli.out.iter().any(|key| {
+            *key != OutputFormatKey::Seed && *key != OutputFormatKey::Sskr
+        }) || (sskr_output && cli.sskr_format != SSKRFormatKey::Ur))
+    {
         bail!(
-            "Input for output form \"{}\" must be random.",
-            output_format.name()
+            "Output for --legacy-ur must be seed, or sskr in the ur SSKR format."
+        );
+    }
+    if cli.sskr_verify && !sskr_output {
+        bail!("Output for --sskr-verify must include sskr.");
+    }
+    if cli.sskr_distribution.is_some() && !sskr_output {
+        bail!("Output for --sskr-distribution must include sskr.");
+    }
+    if cli.sskr_manifest.is_some() && !sskr_output {
+        bail!("Output for --sskr-manifest must include sskr.");
+    }
+    if let Some(path) = &cli.sskr_manifest
+        && cli.out.iter().any(|key| *key != OutputFormatKey::Sskr)
+        && ShareManifest::load(path)?.has_recipients()
+    {
+        eprintln!(
+            "Warning: Only the SSKR shares are encrypted to their custodians. The other outputs are not encrypted."
         );
     }
+    if cli.batch_count.is_some() && input_format.name() != "random" {
+        bail!("Input for --batch-count must be random.");
+    }
+    if cli.batch.is_some() || cli.batch_count.is_some() {
+        return batch::run_batch(cli, input_format.as_ref(), &output_formats);
+    }
+    if cli.sskr_inspect {
+        if cli.r#in != InputFormatKey::Sskr {
+            bail!("Input for --sskr-inspect must be sskr.");
+        }
+        return shares::run_inspect(&cli);
+    }
+    if cli.bip39_recover {
+        if cli.r#in != InputFormatKey::Bip39 {
+            bail!("Input for --bip39-recover must be bip39.");
+        }
+        return recover::run_recover(cli, &output_formats);
+    }
 
-    cli = input_format.process_input(cli)?;
-    let output = output_format.process_output(cli)?;
-    println!("{}", output);
+    input_format.process_input(&mut cli)?;
+    cli.apply_overrides();
+    if let Some(path) = &cli.envelope_check {
+        return check_envelope(path, &cli);
+    }
+    if let Some(dir) = &cli.out_dir {
+        return files::write_output_files(&cli, dir, &output_formats);
+    }
+    let output = if cli.json {
+        let document = json_output(&cli, &output_formats)?;
+        Zeroizing::new(serde_json::to_string_pretty(&document)?)
+    } else {
+        text_output(&cli, &output_formats)?
+    };
+    println!("{}", *output);
 
     Ok(())
 }
@@ -0,0 +1,413 @@
+//! BIP-39 mnemonic helpers: word completion and recovery assistance.
+
+use anyhow::{Result, bail};
+use bc_crypto::sha256;
+use bip39::{Language, Mnemonic};
+use strsim::damerau_levenshtein;
+use zeroize::{Zeroize, Zeroizing};
+
+/// The word counts a BIP-39 mnemonic may have.
+pub const MNEMONIC_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// The number of suggestions offered for a word that isn't in the word list.
+const SUGGESTION_COUNT: usize = 5;
+
+/// Every BIP-39 English word is identified by its first four letters.
+const UNIQUE_PREFIX_LEN: usize = 4;
+
+/// Substitutions further than this from the word they replace are counted
+/// but not listed.
+const MAX_SUBSTITUTION_DISTANCE: usize = 2;
+
+/// The result of matching typed letters against the BIP-39 word list.
+#[derive(Debug, PartialEq, Eq)]
+pub enum WordMatch {
+    Word(&'static str),
+    /// Fewer than four letters that start a word without being one. Even if
+    /// they start only one word, they may be a typo cut short.
+    TooShort,
+    NotFound,
+}
+
+/// Completes a BIP-39 English word from its first four letters, or from the
+/// whole word if it is shorter.
+pub fn complete_word(typed: &str) -> WordMatch {
+    let typed = typed.trim().to_lowercase();
+    if typed.is_empty() {
+        return WordMatch::NotFound;
+    }
+    let words = Language::English.words_by_prefix(&typed);
+    if let Some(word) = words.iter().find(|word| **word == typed) {
+        return WordMatch::Word(word);
+    }
+    match words {
+        [] => WordMatch::NotFound,
+        [word] if typed.chars().count() >= UNIQUE_PREFIX_LEN => {
+            WordMatch::Word(word)
+        }
+        _ => WordMatch::TooShort,
+    }
+}
+
+/// Packs the 11-bit word indices of a mnemonic into a bit string, returning
+/// the entropy and the checksum bits that follow it.
+pub fn split_indices(indices: &[u16]) -> (Zeroizing<Vec<u8>>, u8) {
+    let checksum_bits = indices.len() / 3;
+    let entropy_len = indices.len() * 4 / 3;
+    let mut bits = Zeroizing::new(vec![0u8; entropy_len + 1]);
+    for (i, index) in indices.iter().enumerate() {
+        for j in 0..11 {
+            if index & (1 << (10 - j)) != 0 {
+                let bit = i * 11 + j;
+                bits[bit / 8] |= 1 << (7 - bit % 8);
+            }
+        }
+    }
+    let checksum = bits[entropy_len] >> (8 - checksum_bits);
+    bits.truncate(entropy_len);
+    (bits, checksum)
+}
+
+/// The checksum bits of a mnemonic encoding `entropy`.
+pub fn checksum(entropy: &[u8]) -> u8 {
+    let checksum_bits = entropy.len() / 4;
+    sha256(entropy)[0] >> (8 - checksum_bits)
+}
+
+/// Returns the entropy encoded by a mnemonic given as word indices, or `None`
+/// if its checksum is invalid. The word count must be a valid one.
+///
+/// This is much faster than building and parsing the mnemonic, which matters
+/// when searching through many candidates.
+pub fn entropy_from_indices(indices: &[u16]) -> Option<Zeroizing<Vec<u8>>> {
+    let (entropy, expected) = split_indices(indices);
+    (checksum(&entropy) == expected).then_some(entropy)
+}
+
+/// The words of the list closest to `typed`, closest first.
+fn closest_words(
+    typed: &str,
+    candidates: impl Iterator<Item = &'static str>,
+) -> Vec<&'static str> {
+    let typed = typed.to_lowercase();
+    let mut words: Vec<(usize, &'static str)> = candidates
+        .map(|word| (damerau_levenshtein(&typed, word), word))
+        .collect();
+    words.sort();
+    words.into_iter().map(|(_, word)| word).collect()
+}
+
+fn parse_words(words: &[&str]) -> Option<Mnemonic> {
+    Mnemonic::parse_in_normalized(Language::English, &words.join(" ")).ok()
+}
+
+/// Parses a mnemonic, accepting words abbreviated to their first four
+/// letters. If the
+/// mnemonic is invalid, the error explains which words are wrong and suggests
+/// corrections.
+pub fn parse_with_assist(input: &str) -> Result<Mnemonic> {
+    let typed: Vec<&str> = input.split_whitespace().collect();
+    let matches: Vec<WordMatch> =
+        typed.iter().map(|word| complete_word(word)).collect();
+    let resolved: Vec<Option<&'static str>> = matches
+        .iter()
+        .map(|m| match m {
+            WordMatch::Word(word) => Some(*word),
+            _ => None,
+        })
+        .collect();
+
+    let mut report = Vec::new();
+    if !MNEMONIC_WORD_COUNTS.contains(&typed.len()) {
+        report.push(format!(
+            "The mnemonic has {} words, but must have 12, 15, 18, 21, or 24.",
+            typed.len()
+        ));
+    }
+
+    let invalid_positions: Vec<usize> = (0..typed.len())
+        .filter(|&i| resolved[i].is_none())
+        .collect();
+    for &i in &invalid_positions {
+        let word_list = Language::English.word_list().iter().copied();
+        // The words a short prefix starts are better suggestions than those
+        // merely close to it.
+        let prefix = typed[i].to_lowercase();
+        let word_list: Vec<&str> = match matches[i] {
+            WordMatch::TooShort => word_list
+                .filter(|word| word.starts_with(prefix.as_str()))
+                .collect(),
+            _ => word_list.collect(),
+        };
+        let word_list = word_list.into_iter();
+        let suggestions = if invalid_positions.len() == 1
+            && MNEMONIC_WORD_COUNTS.contains(&typed.len())
+        {
+            // With only one unknown word, only suggest words that give a
+            // valid checksum.
+            let mut words: Vec<&str> =
+                resolved.iter().map(|w| w.unwrap_or_default()).collect();
+            closest_words(
+                typed[i],
+                word_list.filter(|word| {
+                    words[i] = word;
+                    parse_words(&words).is_some()
+                }),
+            )
+        } else {
+            closest_words(typed[i], word_list)
+        };
+        let problem = match matches[i] {
+            WordMatch::TooShort => {
+                "is too short to complete, which needs four letters"
+            }
+            _ => "is not a BIP-39 word",
+        };
+        report.push(format!(
+            "Word {} \"{}\" {}. Did you mean: {}?",
+            i + 1,
+            typed[i],
+            problem,
+            suggestions
+                .iter()
+                .take(SUGGESTION_COUNT)
+                .copied()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    if report.is_empty() {
+        let words: Vec<&str> = resolved.iter().map(|w| w.unwrap()).collect();
+        if let Some(mnemonic) = parse_words(&words) {
+            return Ok(mnemonic);
+        }
+        report.push(
+            "The checksum is invalid, so at least one word is wrong."
+                .to_string(),
+        );
+        report.extend(checksum_substitutions_report(&words));
+    }
+
+    bail!("Invalid BIP-39 mnemonic.\n{}", report.join("\n"));
+}
+
+/// Lists the single-word substitutions that give a valid checksum. Only those
+/// close to the word they replace, and thus likely to be typos, are listed.
+fn checksum_substitutions_report(words: &[&'static str]) -> Vec<String> {
+    let mut substitutions = Vec::new();
+    let mut total = 0;
+    let mut candidate = words.to_vec();
+    for i in 0..words.len() {
+        for word in Language::English.word_list() {
+            if *word == words[i] {
+                continue;
+            }
+            candidate[i] = word;
+            if parse_words(&candidate).is_some() {
+                total += 1;
+                let distance = damerau_levenshtein(words[i], word);
+                if distance <= MAX_SUBSTITUTION_DISTANCE {
+                    substitutions.push((distance, i, *word));
+                }
+            }
+        }
+        candidate[i] = words[i];
+    }
+    substitutions.sort();
+
+    let mut report = Vec::new();
+    if !substitutions.is_empty() {
+        report.push(
+            "Single-word substitutions that give a valid checksum, closest first:"
+                .to_string(),
+        );
+        for (_, i, word) in &substitutions {
+            report.push(format!(
+                "  Word {}: \"{}\" -> \"{}\"",
+                i + 1,
+                words[*i],
+                word
+            ));
+        }
+    }
+    report.push(format!(
+        "{} other single-word substitutions also give a valid checksum.",
+        total - substitutions.len()
+    ));
+    report
+}
+
+/// The final words that give a valid checksum for a mnemonic missing its
+/// last word.
+#[derive(Debug)]
+pub struct LastWords {
+    /// The number of checksum bits in the last word.
+    pub checksum_bits: usize,
+    /// The number of entropy bits in the last word.
+    pub entropy_bits: usize,
+    /// The number of those entropy bits that were given.
+    pub given_bits: usize,
+    /// Every word but the last, with any abbreviations completed.
+    pub words: Vec<&'static str>,
+    /// The final words that give a valid checksum.
+    pub choices: Vec<&'static str>,
+}
+
+/// Finds the final words that complete a mnemonic given all of its other
+/// words. The last word holds both entropy and checksum bits, and `given` is
+/// a string of `0`s and `1`s fixing its leading entropy bits, e.g. from coin
+/// flips.
+pub fn last_words(input: &str, given: &str) -> Result<LastWords> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let word_count = words.len() + 1;
+    if !MNEMONIC_WORD_COUNTS.contains(&word_count) {
+        bail!(
+            "Expected 11, 14, 17, 20, or 23 words, but got {}.",
+            words.len()
+        );
+    }
+    let words = words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| match complete_word(word) {
+            WordMatch::Word(word) => Ok(word),
+            _ => bail!("Word {} \"{}\" is not a BIP-39 word.", i + 1, word),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut indices: Vec<u16> = words
+        .iter()
+        .map(|word| Language::English.find_word(word).unwrap())
+        .collect();
+
+    let checksum_bits = word_count / 3;
+    let entropy_bits = 11 - checksum_bits;
+    if given.len() > entropy_bits || given.chars().any(|c| c != '0' && c != '1')
+    {
+        bail!(
+            "The last word has {} entropy bits, which must be given as up to {} 0s and 1s.",
+            entropy_bits,
+            entropy_bits
+        );
+    }
+    let prefix = u16::from_str_radix(given, 2).unwrap_or(0);
+    let free_bits = entropy_bits - given.len();
+
+    let list = Language::English.word_list();
+    let mut choices = Vec::with_capacity(1 << free_bits);
+    indices.push(0);
+    for free in 0..(1u16 << free_bits) {
+        let entropy = (prefix << free_bits) | free;
+        indices[word_count - 1] = entropy << checksum_bits;
+        let (entropy, _) = split_indices(&indices);
+        let last = indices[word_count - 1] | checksum(&entropy) as u16;
+        choices.push(list[last as usize]);
+    }
+    indices.zeroize();
+
+    Ok(LastWords {
+        checksum_bits,
+        entropy_bits,
+        given_bits: given.len(),
+        words,
+        choices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "matrix pull accuse apart horn chat next rifle resemble artist until eye";
+
+    #[test]
+    fn test_complete_word() {
+        assert_eq!(complete_word("abandon"), WordMatch::Word("abandon"));
+        assert_eq!(complete_word("aban"), WordMatch::Word("abandon"));
+        assert_eq!(complete_word(" ZOO "), WordMatch::Word("zoo"));
+        // "act" is a word, and also a prefix of "action", "actor", ...
+        assert_eq!(complete_word("act"), WordMatch::Word("act"));
+        assert_eq!(complete_word("acti"), WordMatch::Word("action"));
+        assert_eq!(complete_word("ab"), WordMatch::TooShort);
+        // Only "oxygen" starts with "oxy", but a cut-short typo isn't
+        // completed.
+        assert_eq!(complete_word("oxy"), WordMatch::TooShort);
+        assert_eq!(complete_word("oxyg"), WordMatch::Word("oxygen"));
+        assert_eq!(complete_word("xyz"), WordMatch::NotFound);
+        assert_eq!(complete_word(""), WordMatch::NotFound);
+    }
+
+    #[test]
+    fn test_prefixes() {
+        let mnemonic = parse_with_assist(
+            "matr pull accu apar horn chat next rifl rese arti unti eye",
+        )
+        .unwrap();
+        assert_eq!(mnemonic.to_string(), MNEMONIC);
+
+        // A prefix shorter than four letters is not completed, but the words
+        // it starts are suggested.
+        let error = parse_with_assist(
+            "mat pull accu apar horn chat next rifl rese arti unti eye",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid BIP-39 mnemonic.\nWord 1 \"mat\" is too short to complete, which needs four letters. Did you mean: matrix?"
+        );
+    }
+
+    #[test]
+    fn test_entropy_from_indices() {
+        let mnemonic = Mnemonic::parse_normalized(MNEMONIC).unwrap();
+        let mut indices: Vec<u16> =
+            mnemonic.word_indices().map(|i| i as u16).collect();
+        assert_eq!(
+            *entropy_from_indices(&indices).unwrap(),
+            mnemonic.to_entropy()
+        );
+        indices.swap(0, 1);
+        assert!(entropy_from_indices(&indices).is_none());
+    }
+
+    #[test]
+    fn test_last_words() {
+        let words = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        let last = last_words(words, "").unwrap();
+        assert_eq!(last.checksum_bits, 4);
+        assert_eq!(last.entropy_bits, 7);
+        assert_eq!(last.choices.len(), 128);
+        assert_eq!(last.choices[0], "about");
+        for word in &last.choices {
+            let mnemonic = format!("{} {}", words, word);
+            assert!(Mnemonic::parse_normalized(&mnemonic).is_ok());
+        }
+
+        let last = last_words(words, "0000000").unwrap();
+        assert_eq!(last.choices, ["about"]);
+
+        let words = [words, words].join(" ") + " abandon";
+        let last = last_words(&words, "1").unwrap();
+        assert_eq!((last.checksum_bits, last.choices.len()), (8, 4));
+
+        assert!(last_words(words.as_str(), "0000").is_err());
+        assert!(last_words("abandon abandon", "").is_err());
+    }
+
+    #[test]
+    fn test_misspelled_word() {
+        let input = MNEMONIC.replace("rifle", "rilfe");
+        let err = parse_with_assist(&input).unwrap_err().to_string();
+        assert!(err.contains(
+            "Word 8 \"rilfe\" is not a BIP-39 word. Did you mean: rifle"
+        ));
+    }
+
+    #[test]
+    fn test_checksum() {
+        let input = MNEMONIC.replace("horn", "horse");
+        let err = parse_with_assist(&input).unwrap_err().to_string();
+        assert!(err.contains("The checksum is invalid"));
+        assert!(err.contains("Word 5: \"horse\" -> \"horn\""));
+    }
+}
@@ -0,0 +1,65 @@
+//! Rendering of the requested outputs, either as plain text or as JSON.
+//!
+//! When `--json` is given, seedtool prints a JSON object instead of plain
+//! text. The schema is documented in `USAGE.md`. New fields may be added
+//! without changing `JSON_SCHEMA_VERSION`; removing a field or changing its
+//! meaning requires incrementing it.
+
+use anyhow::Result;
+use serde_json::{Value, json};
+use zeroize::Zeroizing;
+
+use crate::{cli::Cli, formats::OutputFormat, seed::Seed};
+
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// A single output is rendered as-is. Multiple outputs are rendered as
+/// sections, each headed by the name of its format.
+pub fn text_output(
+    state: &Cli,
+    output_formats: &[Box<dyn OutputFormat>],
+) -> Result<Zeroizing<String>> {
+    let mut outputs = output_formats
+        .iter()
+        .map(|output_format| {
+            let output = Zeroizing::new(output_format.process_output(state)?);
+            Ok((output_format.name(), output))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if outputs.len() == 1 {
+        return Ok(outputs.remove(0).1);
+    }
+    let mut text = Zeroizing::new(String::new());
+    for (i, (name, output)) in outputs.iter().enumerate() {
+        if i > 0 {
+            text.push_str("\n\n");
+        }
+        text.push_str(&format!("# {}\n", name));
+        text.push_str(output);
+    }
+    Ok(text)
+}
+
+pub fn json_output(
+    state: &Cli,
+    output_formats: &[Box<dyn OutputFormat>],
+) -> Result<Value> {
+    let outputs = output_formats
+        .iter()
+        .map(|output_format| output_format.process_output_json(state))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(json!({
+        "version": JSON_SCHEMA_VERSION,
+        "seed": seed_metadata_json(state.expect_seed()),
+        "outputs": outputs,
+    }))
+}
+
+fn seed_metadata_json(seed: &Seed) -> Value {
+    let optional = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    json!({
+        "name": optional(seed.name()),
+        "note": optional(seed.note()),
+        "date": seed.creation_date().map(|date| date.to_string()),
+    })
+}
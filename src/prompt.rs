@@ -0,0 +1,84 @@
+//! Interactive prompts for secrets.
+//!
+//! Secrets are read from the terminal with echo disabled, so they don't appear
+//! on screen, in shell history, or in the process's arguments.
+
+use anyhow::{Result, anyhow, bail};
+use zeroize::Zeroizing;
+
+use crate::{
+    formats::ShareCollector,
+    mnemonic::{MNEMONIC_WORD_COUNTS, WordMatch, complete_word},
+    seed::Seed,
+};
+
+pub fn prompt_secret(prompt: &str) -> Result<Zeroizing<String>> {
+    let secret = Zeroizing::new(
+        rpassword::prompt_password(prompt)
+            .map_err(|e| anyhow!("Unable to prompt on the terminal: {}", e))?,
+    );
+    Ok(Zeroizing::new(secret.trim().to_string()))
+}
+
+/// Prompts for a BIP-39 passphrase. Unlike other secrets it isn't trimmed,
+/// since leading and trailing spaces are part of the passphrase.
+pub fn prompt_passphrase() -> Result<Zeroizing<String>> {
+    Ok(Zeroizing::new(
+        rpassword::prompt_password("BIP-39 passphrase: ")
+            .map_err(|e| anyhow!("Unable to prompt on the terminal: {}", e))?,
+    ))
+}
+
+/// Prompts for a BIP-39 mnemonic one word at a time.
+pub fn prompt_bip39_words() -> Result<Zeroizing<String>> {
+    eprintln!(
+        "Enter each word of the mnemonic. Words may be abbreviated to their first four letters."
+    );
+    eprintln!("Enter an empty line after the last word.");
+    let max_count = *MNEMONIC_WORD_COUNTS.last().unwrap();
+    let mut words: Vec<&'static str> = Vec::new();
+    while words.len() < max_count {
+        let typed = prompt_secret(&format!("Word {}: ", words.len() + 1))?;
+        if typed.is_empty() {
+            if MNEMONIC_WORD_COUNTS.contains(&words.len()) {
+                break;
+            }
+            eprintln!("A mnemonic has 12, 15, 18, 21, or 24 words.");
+            continue;
+        }
+        match complete_word(&typed) {
+            WordMatch::Word(word) => words.push(word),
+            WordMatch::TooShort => {
+                eprintln!("Type at least four letters, or the whole word.")
+            }
+            WordMatch::NotFound => {
+                eprintln!("That isn't a BIP-39 word. Try again.")
+            }
+        }
+    }
+    Ok(Zeroizing::new(words.join(" ")))
+}
+
+/// Prompts for SSKR shares one at a time, checking each as it is entered and
+/// showing the progress toward recovery, until the seed can be recovered.
+pub fn prompt_sskr_seed(collector: &mut ShareCollector) -> Result<Seed> {
+    eprintln!("Enter each share as it arrives.");
+    eprintln!("Enter an empty line to stop.");
+    loop {
+        let typed = prompt_secret(&format!("Share {}: ", collector.len() + 1))?;
+        if typed.is_empty() {
+            bail!(
+                "Stopped before enough shares were entered.\n{}",
+                collector.progress()
+            );
+        }
+        match collector.add(&typed) {
+            Ok(Some(seed)) => {
+                eprintln!("The seed has been recovered.");
+                return Ok(seed);
+            }
+            Ok(None) => eprintln!("{}", collector.progress()),
+            Err(error) => eprintln!("Share not accepted: {}", error),
+        }
+    }
+}
@@ -3,6 +3,7 @@ use bc_crypto::{
     SHA256_SIZE,
     hash::{hkdf_hmac_sha256, sha256},
 };
+use zeroize::Zeroize;
 
 #[derive(Debug, Clone)]
 pub struct DeterministicRandomNumberGenerator {
@@ -26,6 +27,10 @@ impl DeterministicRandomNumberGenerator {
     }
 }
 
+impl Drop for DeterministicRandomNumberGenerator {
+    fn drop(&mut self) { self.seed.zeroize(); }
+}
+
 pub fn sha256_deterministic_random(
     entropy: &[u8],
     n: usize,
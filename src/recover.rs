@@ -0,0 +1,325 @@
+//! Brute-force recovery of BIP-39 mnemonics with missing or misplaced words.
+
+use std::{
+    io::IsTerminal,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, bail};
+use bip39::{Language, Mnemonic};
+use bitcoin::secp256k1::{All, Secp256k1};
+use serde_json::json;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    bip32::{Address, MasterKey},
+    cli::Cli,
+    formats::OutputFormat,
+    mnemonic::{
+        MNEMONIC_WORD_COUNTS, WordMatch, complete_word, entropy_from_indices,
+    },
+    output::{json_output, text_output},
+    prompt::prompt_passphrase,
+    seed::Seed,
+};
+
+/// The number of candidates each thread takes from the search space at a
+/// time.
+const CHUNK_SIZE: u64 = 1024;
+
+/// The candidate mnemonics to be checked, as word indices.
+enum SearchSpace {
+    /// Every combination of the possible words at each placeholder.
+    Placeholders {
+        indices: Vec<u16>,
+        positions: Vec<usize>,
+        options: Vec<Vec<u16>>,
+    },
+    /// The mnemonic with each pair of differing words swapped.
+    Swaps {
+        indices: Vec<u16>,
+        pairs: Vec<(usize, usize)>,
+    },
+}
+
+impl SearchSpace {
+    fn parse(input: &str) -> Result<Self> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        if !MNEMONIC_WORD_COUNTS.contains(&words.len()) {
+            bail!(
+                "The mnemonic has {} words, but must have 12, 15, 18, 21, or 24.",
+                words.len()
+            );
+        }
+        let mut indices = Vec::with_capacity(words.len());
+        let mut positions = Vec::new();
+        let mut options = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            if let Some(prefix) = word.strip_suffix('?') {
+                let prefix = prefix.to_lowercase();
+                let candidates: Vec<u16> = Language::English
+                    .words_by_prefix(&prefix)
+                    .iter()
+                    .map(|word| Language::English.find_word(word).unwrap())
+                    .collect();
+                if candidates.is_empty() {
+                    bail!(
+                        "Word {}: no BIP-39 word starts with \"{}\".",
+                        i + 1,
+                        prefix
+                    );
+                }
+                indices.push(candidates[0]);
+                positions.push(i);
+                options.push(candidates);
+            } else {
+                match complete_word(word) {
+                    WordMatch::Word(word) => {
+                        indices.push(Language::English.find_word(word).unwrap())
+                    }
+                    _ => bail!(
+                        "Word {} \"{}\" is not a BIP-39 word. Replace it with ? to search for it.",
+                        i + 1,
+                        word
+                    ),
+                }
+            }
+        }
+        if positions.is_empty() {
+            let pairs = (0..indices.len())
+                .flat_map(|i| (i + 1..indices.len()).map(move |j| (i, j)))
+                .filter(|(i, j)| indices[*i] != indices[*j])
+                .collect();
+            Ok(Self::Swaps { indices, pairs })
+        } else {
+            Ok(Self::Placeholders {
+                indices,
+                positions,
+                options,
+            })
+        }
+    }
+
+    fn len(&self) -> Result<u64> {
+        match self {
+            Self::Placeholders { options, .. } => options
+                .iter()
+                .try_fold(1u64, |len, options| {
+                    len.checked_mul(options.len() as u64)
+                })
+                .ok_or_else(|| anyhow::anyhow!("Too many unknown words.")),
+            Self::Swaps { pairs, .. } => Ok(pairs.len() as u64),
+        }
+    }
+
+    /// Writes the word indices of candidate `n` to `indices`.
+    fn candidate(&self, mut n: u64, indices: &mut Vec<u16>) {
+        indices.clear();
+        match self {
+            Self::Placeholders {
+                indices: base,
+                positions,
+                options,
+            } => {
+                indices.extend_from_slice(base);
+                for (position, options) in positions.iter().zip(options).rev() {
+                    let len = options.len() as u64;
+                    indices[*position] = options[(n % len) as usize];
+                    n /= len;
+                }
+            }
+            Self::Swaps {
+                indices: base,
+                pairs,
+            } => {
+                indices.extend_from_slice(base);
+                let (i, j) = pairs[n as usize];
+                indices.swap(i, j);
+            }
+        }
+    }
+}
+
+/// Conditions a candidate must meet beyond a valid checksum.
+struct Filter<'a> {
+    fingerprint: Option<[u8; 4]>,
+    address: Option<Address>,
+    passphrase: &'a str,
+}
+
+impl Filter<'_> {
+    fn is_empty(&self) -> bool {
+        self.fingerprint.is_none() && self.address.is_none()
+    }
+
+    fn matches(&self, secp: &Secp256k1<All>, entropy: &[u8]) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let mnemonic = Mnemonic::from_entropy(entropy).unwrap();
+        let mut seed = mnemonic.to_seed(self.passphrase);
+        let master = MasterKey::new(&seed);
+        seed.zeroize();
+        // An invalid key is astronomically unlikely, and can't be a match.
+        let Ok(master) = master else {
+            return false;
+        };
+        if let Some(fingerprint) = self.fingerprint
+            && master.fingerprint(secp) != fingerprint
+        {
+            return false;
+        }
+        if let Some(address) = &self.address
+            && !address.matches(secp, &master).unwrap_or(false)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Checks every candidate in the search space, returning the entropy of each
+/// that matches, in search order.
+fn search(
+    space: &SearchSpace,
+    filter: &Filter<'_>,
+    threads: usize,
+) -> Result<Vec<Zeroizing<Vec<u8>>>> {
+    let len = space.len()?;
+    let next = AtomicU64::new(0);
+    let searched = AtomicU64::new(0);
+    let finished = AtomicUsize::new(0);
+    let found = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let secp = Secp256k1::new();
+                let mut indices = Vec::new();
+                loop {
+                    let start = next.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+                    if start >= len {
+                        break;
+                    }
+                    let end = (start + CHUNK_SIZE).min(len);
+                    for n in start..end {
+                        space.candidate(n, &mut indices);
+                        if let Some(entropy) = entropy_from_indices(&indices)
+                            && filter.matches(&secp, &entropy)
+                        {
+                            found.lock().unwrap().push((n, entropy));
+                        }
+                    }
+                    searched.fetch_add(end - start, Ordering::Relaxed);
+                }
+                indices.zeroize();
+                finished.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        report_progress(len, &searched, &finished, threads, &found);
+    });
+
+    let mut found = found.into_inner().unwrap();
+    found.sort_by_key(|(n, _)| *n);
+    Ok(found.into_iter().map(|(_, entropy)| entropy).collect())
+}
+
+/// Reports progress on stderr until every thread has finished, if stderr is a
+/// terminal and the search takes more than a moment.
+fn report_progress<T>(
+    len: u64,
+    searched: &AtomicU64,
+    finished: &AtomicUsize,
+    threads: usize,
+    found: &Mutex<Vec<T>>,
+) {
+    let start = Instant::now();
+    let interactive = std::io::stderr().is_terminal();
+    let mut reported = false;
+    while finished.load(Ordering::Relaxed) < threads {
+        thread::sleep(Duration::from_millis(100));
+        if !interactive || start.elapsed() < Duration::from_secs(1) {
+            continue;
+        }
+        let searched = searched.load(Ordering::Relaxed);
+        let elapsed = start.elapsed().as_secs_f64();
+        let remaining =
+            (len - searched) as f64 * elapsed / searched.max(1) as f64;
+        eprint!(
+            "\rSearched {} of {} ({:.1}%), {} found, about {:.0}s remaining   ",
+            searched,
+            len,
+            searched as f64 * 100.0 / len as f64,
+            found.lock().unwrap().len(),
+            remaining
+        );
+        reported = true;
+    }
+    if reported {
+        eprintln!();
+    }
+}
+
+/// Searches for the mnemonics matching the input, and outputs each one
+/// found. In JSON mode each output is a single line with a `candidate` number
+/// added.
+pub fn run_recover(
+    mut state: Cli,
+    output_formats: &[Box<dyn OutputFormat>],
+) -> Result<()> {
+    let space = SearchSpace::parse(&state.expect_input()?)?;
+    let address = state.address.as_deref().map(Address::parse).transpose()?;
+    let filtered = state.fingerprint.is_some() || address.is_some();
+    let passphrase = match &state.passphrase {
+        Some(Some(passphrase)) => Zeroizing::new(passphrase.clone()),
+        Some(None) => prompt_passphrase()?,
+        None if state.prompt && filtered => prompt_passphrase()?,
+        None => Zeroizing::new(String::new()),
+    };
+    let filter = Filter {
+        fingerprint: state.fingerprint,
+        address,
+        passphrase: &passphrase,
+    };
+    let threads = state.threads.unwrap_or_else(|| {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    });
+    eprintln!(
+        "Searching {} candidate mnemonics using {} thread(s).",
+        space.len()?,
+        threads
+    );
+    let found = search(&space, &filter, threads)?;
+    if found.is_empty() {
+        if filter.is_empty() {
+            bail!("No candidate mnemonic has a valid checksum.");
+        }
+        bail!(
+            "No candidate mnemonic has a valid checksum and matches the filter."
+        );
+    }
+    eprintln!("Found {} candidate mnemonic(s).", found.len());
+
+    for (n, entropy) in found.iter().enumerate() {
+        state.seed = Some(Seed::new(entropy.as_slice()));
+        state.apply_overrides();
+        if state.json {
+            let mut document = json_output(&state, output_formats)?;
+            document["candidate"] = json!(n + 1);
+            let output = Zeroizing::new(serde_json::to_string(&document)?);
+            println!("{}", *output);
+        } else {
+            let output = text_output(&state, output_formats)?;
+            println!("{}", *output);
+            if output.contains('\n') {
+                println!();
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Best-effort protection of secret material held in memory.
+//!
+//! None of these measures are guaranteed: each is silently skipped on
+//! platforms that don't support it, or when the OS refuses (e.g., because
+//! `RLIMIT_MEMLOCK` is too low).
+
+/// Prevents the process from writing a core dump, which could contain
+/// secrets, if it crashes.
+pub fn disable_core_dumps() {
+    #[cfg(unix)]
+    unsafe {
+        let limit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        libc::setrlimit(libc::RLIMIT_CORE, &limit);
+    }
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::prctl(libc::PR_SET_DUMPABLE, 0);
+    }
+}
+
+/// Asks the OS to keep the pages holding `data` out of swap.
+pub fn lock_memory(data: &[u8]) {
+    #[cfg(unix)]
+    if !data.is_empty() {
+        unsafe {
+            libc::mlock(data.as_ptr().cast(), data.len());
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = data;
+}
+
+/// Releases a lock taken by `lock_memory`.
+pub fn unlock_memory(data: &[u8]) {
+    #[cfg(unix)]
+    if !data.is_empty() {
+        unsafe {
+            libc::munlock(data.as_ptr().cast(), data.len());
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = data;
+}
@@ -1,20 +1,36 @@
 use anyhow::{Error, Result};
-use bc_components::{Seed as ComponentsSeed, tags};
+use bc_components::{DigestProvider, Seed as ComponentsSeed, tags};
 use bc_envelope::{Envelope, known_values};
 use dcbor::prelude::*;
+use zeroize::Zeroize;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+use crate::secure::{lock_memory, unlock_memory};
+
+/// A seed and its metadata.
+///
+/// The seed data is locked in memory where supported, and zeroed when the
+/// seed is dropped. `Seed` is deliberately not `Clone`, so that copies of the
+/// data aren't made by accident.
+#[derive(Debug, PartialEq, Eq)]
 pub struct Seed {
     data: Vec<u8>,
     name: String, // Empty string: no name
     note: String, // Empty string: no note
     creation_date: Option<Date>,
+    /// The assertions of the seed's envelope other than its type, name,
+    /// note, and date, such as attachments, so that they can be output
+    /// again.
+    assertions: Vec<Envelope>,
+    /// The type, name, note, and date assertions of the seed's envelope that
+    /// were salted, so that the same assertions are output with the same
+    /// salt, and so the same digests.
+    salted_assertions: Vec<Envelope>,
 }
 
 impl Seed {
     pub fn new<T>(data: T) -> Self
     where
-        T: AsRef<[u8]>,
+        T: Into<Vec<u8>>,
     {
         Self::new_opt(data, String::new(), String::new(), None)
     }
@@ -26,15 +42,21 @@ impl Seed {
         creation_date: Option<Date>,
     ) -> Self
     where
-        T: AsRef<[u8]>,
+        T: Into<Vec<u8>>,
         S: AsRef<str>,
         U: AsRef<str>,
     {
+        // Taking ownership means a `Vec<u8>` becomes the seed's own buffer
+        // rather than being copied and left behind.
+        let data = data.into();
+        lock_memory(&data);
         Self {
-            data: data.as_ref().to_vec(),
+            data,
             name: name.as_ref().to_string(),
             note: note.as_ref().to_string(),
             creation_date,
+            assertions: Vec::new(),
+            salted_assertions: Vec::new(),
         }
     }
 
@@ -57,6 +79,36 @@ impl Seed {
     pub fn set_creation_date(&mut self, creation_date: Option<Date>) {
         self.creation_date = creation_date;
     }
+
+    pub fn assertions(&self) -> &[Envelope] { &self.assertions }
+
+    pub fn set_assertions(&mut self, assertions: Vec<Envelope>) {
+        self.assertions = assertions;
+    }
+
+    pub fn add_assertion(&mut self, assertion: Envelope) {
+        self.assertions.push(assertion);
+    }
+
+    pub fn set_salted_assertions(&mut self, salted_assertions: Vec<Envelope>) {
+        self.salted_assertions = salted_assertions;
+    }
+
+    /// The assertion, or its salted form if the seed was read with one.
+    fn salted(&self, assertion: Envelope) -> Envelope {
+        self.salted_assertions
+            .iter()
+            .find(|salted| salted.subject().digest() == assertion.digest())
+            .cloned()
+            .unwrap_or(assertion)
+    }
+}
+
+impl Drop for Seed {
+    fn drop(&mut self) {
+        self.data.as_mut_slice().zeroize();
+        unlock_memory(&self.data);
+    }
 }
 
 impl CBORTagged for Seed {
@@ -67,6 +119,10 @@ impl From<Seed> for CBOR {
     fn from(value: Seed) -> Self { value.tagged_cbor() }
 }
 
+impl From<&Seed> for CBOR {
+    fn from(value: &Seed) -> Self { value.tagged_cbor() }
+}
+
 impl CBORTaggedEncodable for Seed {
     fn untagged_cbor(&self) -> CBOR {
         let mut map = Map::new();
@@ -109,21 +165,64 @@ impl CBORTaggedDecodable for Seed {
     }
 }
 
-impl From<Seed> for Envelope {
-    fn from(seed: Seed) -> Self {
-        let mut e = Envelope::new(CBOR::to_byte_string(seed.data()))
-            .add_type(known_values::SEED_TYPE)
-            .add_optional_assertion(known_values::DATE, seed.creation_date());
+impl Seed {
+    pub fn to_envelope(&self) -> Envelope { Envelope::from(self) }
+}
+
+impl From<&Seed> for Envelope {
+    fn from(seed: &Seed) -> Self {
+        let mut assertions = vec![Envelope::new_assertion(
+            known_values::IS_A,
+            known_values::SEED_TYPE,
+        )];
+
+        if let Some(creation_date) = seed.creation_date() {
+            assertions.push(Envelope::new_assertion(
+                known_values::DATE,
+                creation_date,
+            ));
+        }
 
         if !seed.name().is_empty() {
-            e = e.add_assertion(known_values::NAME, seed.name());
+            assertions
+                .push(Envelope::new_assertion(known_values::NAME, seed.name()));
         }
 
         if !seed.note().is_empty() {
-            e = e.add_assertion(known_values::NOTE, seed.note());
+            assertions
+                .push(Envelope::new_assertion(known_values::NOTE, seed.note()));
         }
 
-        e
+        let mut assertions: Vec<_> = assertions
+            .into_iter()
+            .map(|assertion| seed.salted(assertion))
+            .collect();
+        assertions.extend_from_slice(seed.assertions());
+
+        // Every one of these is an assertion, or was taken from an envelope
+        // as one, so none can fail to be added.
+        Envelope::new(CBOR::to_byte_string(seed.data()))
+            .add_assertion_envelopes(&assertions)
+            .expect("seed assertions are assertions")
+    }
+}
+
+/// Whether the assertion is one that `Seed` holds in its own fields.
+pub fn is_seed_assertion(assertion: &Envelope) -> bool {
+    let (Some(predicate), Some(object)) =
+        (assertion.as_predicate(), assertion.as_object())
+    else {
+        return false;
+    };
+    match predicate.as_known_value() {
+        Some(value) if *value == known_values::IS_A => {
+            object.as_known_value() == Some(&known_values::SEED_TYPE)
+        }
+        Some(value) => {
+            [known_values::NAME, known_values::NOTE, known_values::DATE]
+                .contains(value)
+        }
+        None => false,
     }
 }
 
@@ -153,7 +252,23 @@ impl TryFrom<Envelope> for Seed {
             .extract_optional_object_for_predicate::<Date>(
                 known_values::DATE,
             )?;
-        Ok(Self::new_opt(data, name, note, creation_date))
+        let mut seed = Self::new_opt(data, name, note, creation_date);
+        let (seed_assertions, assertions): (Vec<_>, Vec<_>) = envelope
+            .assertions()
+            .into_iter()
+            .partition(is_seed_assertion);
+        seed.set_assertions(assertions);
+        seed.set_salted_assertions(
+            seed_assertions
+                .into_iter()
+                .filter(|assertion| {
+                    !assertion
+                        .assertions_with_predicate(known_values::SALT)
+                        .is_empty()
+                })
+                .collect(),
+        );
+        Ok(seed)
     }
 }
 
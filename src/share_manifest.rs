@@ -0,0 +1,334 @@
+//! Labels, instructions, and contacts added to each SSKR share envelope, read
+//! from a share manifest file.
+
+use std::fs;
+
+use anyhow::{Result, anyhow, bail};
+use bc_components::PublicKeys;
+use bc_envelope::prelude::*;
+use serde_json::{Map, Value};
+use sskr::Spec;
+
+/// The assertions added to a single share envelope, and the public key it is
+/// encrypted to.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShareLabels {
+    pub custodian: Option<String>,
+    pub group: Option<String>,
+    pub member: Option<String>,
+    pub instructions: Option<String>,
+    pub contact: Option<String>,
+    pub recipient: Option<PublicKeys>,
+}
+
+impl ShareLabels {
+    /// Encrypts the share envelope to the recipient, if any, and then adds
+    /// the labels, so that they can be read without decrypting the share.
+    pub fn apply(&self, envelope: Envelope) -> Envelope {
+        let envelope = match &self.recipient {
+            Some(recipient) => envelope.encrypt_to_recipient(recipient),
+            None => envelope,
+        };
+        envelope
+            .add_optional_assertion("custodian", self.custodian.clone())
+            .add_optional_assertion("group", self.group.clone())
+            .add_optional_assertion("member", self.member.clone())
+            .add_optional_assertion("instructions", self.instructions.clone())
+            .add_optional_assertion("contact", self.contact.clone())
+    }
+}
+
+#[derive(Debug, Default)]
+struct MemberEntry {
+    label: Option<String>,
+    custodian: Option<String>,
+    instructions: Option<String>,
+    contact: Option<String>,
+    recipient: Option<PublicKeys>,
+}
+
+#[derive(Debug, Default)]
+struct GroupEntry {
+    label: Option<String>,
+    instructions: Option<String>,
+    contact: Option<String>,
+    members: Vec<MemberEntry>,
+}
+
+/// A JSON object describing who holds each share. The `instructions` and
+/// `contact` at the top level apply to every share unless a group or member
+/// gives its own.
+///
+/// ```json
+/// {
+///   "instructions": "Recover with seedtool --in sskr.",
+///   "contact": "estate@example.com",
+///   "groups": [
+///     {
+///       "label": "Family",
+///       "members": [
+///         { "custodian": "Alice", "label": "Alice's share" },
+///         { "custodian": "Bob", "recipient": "ur:crypto-pubkeys/..." }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ShareManifest {
+    instructions: Option<String>,
+    contact: Option<String>,
+    groups: Vec<GroupEntry>,
+}
+
+/// Removes the fields of a manifest object one at a time, so that any left
+/// over can be reported as unknown.
+struct Fields<'a> {
+    object: Map<String, Value>,
+    context: &'a str,
+}
+
+impl<'a> Fields<'a> {
+    fn new(value: Value, context: &'a str) -> Result<Self> {
+        match value {
+            Value::Object(object) => Ok(Self { object, context }),
+            _ => bail!("{} must be a JSON object.", context),
+        }
+    }
+
+    fn string(&mut self, key: &str) -> Result<Option<String>> {
+        match self.object.remove(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(_) => {
+                bail!("{} field \"{}\" must be a string.", self.context, key)
+            }
+        }
+    }
+
+    fn public_keys(&mut self, key: &str) -> Result<Option<PublicKeys>> {
+        self.string(key)?
+            .map(|s| {
+                PublicKeys::from_ur_string(s.trim()).map_err(|_| {
+                    anyhow!(
+                        "{} field \"{}\" must be a ur:crypto-pubkeys.",
+                        self.context,
+                        key
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    fn array(&mut self, key: &str) -> Result<Vec<Value>> {
+        match self.object.remove(key) {
+            None | Some(Value::Null) => Ok(Vec::new()),
+            Some(Value::Array(values)) => Ok(values),
+            Some(_) => {
+                bail!("{} field \"{}\" must be an array.", self.context, key)
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        if let Some(key) = self.object.keys().next() {
+            bail!("{} has an unknown field \"{}\".", self.context, key);
+        }
+        Ok(())
+    }
+}
+
+impl ShareManifest {
+    pub fn load(path: &str) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            anyhow!("Can't read the share manifest \"{}\": {}", path, e)
+        })?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| anyhow!("Share manifest is not valid JSON: {}", e))?;
+        let mut fields = Fields::new(value, "Share manifest")?;
+        let mut manifest = Self {
+            instructions: fields.string("instructions")?,
+            contact: fields.string("contact")?,
+            groups: Vec::new(),
+        };
+        for (i, group) in fields.array("groups")?.into_iter().enumerate() {
+            let context = format!("Share manifest group {}", i + 1);
+            let mut fields = Fields::new(group, &context)?;
+            let mut entry = GroupEntry {
+                label: fields.string("label")?,
+                instructions: fields.string("instructions")?,
+                contact: fields.string("contact")?,
+                members: Vec::new(),
+            };
+            for (j, member) in fields.array("members")?.into_iter().enumerate()
+            {
+                let context =
+                    format!("Share manifest group {} member {}", i + 1, j + 1);
+                let mut fields = Fields::new(member, &context)?;
+                entry.members.push(MemberEntry {
+                    label: fields.string("label")?,
+                    custodian: fields.string("custodian")?,
+                    instructions: fields.string("instructions")?,
+                    contact: fields.string("contact")?,
+                    recipient: fields.public_keys("recipient")?,
+                });
+                fields.finish()?;
+            }
+            fields.finish()?;
+            manifest.groups.push(entry);
+        }
+        fields.finish()?;
+        Ok(manifest)
+    }
+
+    /// Whether any share is encrypted to its custodian.
+    pub fn has_recipients(&self) -> bool {
+        self.groups
+            .iter()
+            .flat_map(|group| &group.members)
+            .any(|member| member.recipient.is_some())
+    }
+
+    /// Checks that the manifest describes the groups and members of the
+    /// spec. Groups and members may be omitted entirely, but if any are
+    /// listed they all must be.
+    pub fn check(&self, spec: &Spec) -> Result<()> {
+        if self.groups.is_empty() {
+            return Ok(());
+        }
+        if self.groups.len() != spec.group_count() {
+            bail!(
+                "The share manifest lists {} groups, but the SSKR spec has {}.",
+                self.groups.len(),
+                spec.group_count()
+            );
+        }
+        for (i, (entry, group)) in
+            self.groups.iter().zip(spec.groups()).enumerate()
+        {
+            if !entry.members.is_empty()
+                && entry.members.len() != group.member_count()
+            {
+                bail!(
+                    "The share manifest lists {} members for group {}, but the SSKR spec has {}.",
+                    entry.members.len(),
+                    i + 1,
+                    group.member_count()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The labels for the given share, with the instructions and contact of
+    /// the member taking precedence over those of its group, and those of
+    /// the group over the manifest's.
+    pub fn labels(
+        &self,
+        group_index: usize,
+        member_index: usize,
+    ) -> ShareLabels {
+        let group = self.groups.get(group_index);
+        let member = group.and_then(|group| group.members.get(member_index));
+        let inherited = |member: Option<&Option<String>>,
+                         group: Option<&Option<String>>,
+                         manifest: &Option<String>| {
+            member
+                .and_then(Option::clone)
+                .or_else(|| group.and_then(Option::clone))
+                .or_else(|| manifest.clone())
+        };
+        ShareLabels {
+            custodian: member.and_then(|m| m.custodian.clone()),
+            group: group.and_then(|g| g.label.clone()),
+            member: member.and_then(|m| m.label.clone()),
+            instructions: inherited(
+                member.map(|m| &m.instructions),
+                group.map(|g| &g.instructions),
+                &self.instructions,
+            ),
+            contact: inherited(
+                member.map(|m| &m.contact),
+                group.map(|g| &g.contact),
+                &self.contact,
+            ),
+            recipient: member.and_then(|m| m.recipient.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use sskr::GroupSpec;
+
+    use super::*;
+
+    #[test]
+    fn test_share_manifest() {
+        let manifest = ShareManifest::parse(indoc! {r#"
+            {
+                "instructions": "Recover with seedtool.",
+                "contact": "estate@example.com",
+                "groups": [
+                    {
+                        "label": "Family",
+                        "members": [
+                            { "custodian": "Alice", "label": "Alice's share" },
+                            { "custodian": "Bob", "contact": "bob@example.com" },
+                            { "custodian": "Carol" }
+                        ]
+                    },
+                    { "label": "Lawyers", "instructions": "Ask the family first." }
+                ]
+            }
+        "#})
+        .unwrap();
+        let spec = Spec::new(
+            2,
+            vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(1, 2).unwrap()],
+        )
+        .unwrap();
+        manifest.check(&spec).unwrap();
+        assert!(!manifest.has_recipients());
+
+        assert_eq!(
+            manifest.labels(0, 1),
+            ShareLabels {
+                custodian: Some("Bob".to_string()),
+                group: Some("Family".to_string()),
+                member: None,
+                instructions: Some("Recover with seedtool.".to_string()),
+                contact: Some("bob@example.com".to_string()),
+                recipient: None,
+            }
+        );
+        assert_eq!(
+            manifest.labels(1, 0),
+            ShareLabels {
+                custodian: None,
+                group: Some("Lawyers".to_string()),
+                member: None,
+                instructions: Some("Ask the family first.".to_string()),
+                contact: Some("estate@example.com".to_string()),
+                recipient: None,
+            }
+        );
+
+        let spec = Spec::new(1, vec![GroupSpec::new(2, 3).unwrap()]).unwrap();
+        assert_eq!(
+            manifest.check(&spec).unwrap_err().to_string(),
+            "The share manifest lists 2 groups, but the SSKR spec has 1."
+        );
+        assert_eq!(
+            ShareManifest::parse(r#"{ "groups": [{ "custodian": "Alice" }] }"#)
+                .unwrap_err()
+                .to_string(),
+            "Share manifest group 1 has an unknown field \"custodian\"."
+        );
+    }
+}
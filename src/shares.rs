@@ -0,0 +1,445 @@
+//! Analysis of a collection of SSKR shares, without recovering the secret.
+
+use anyhow::{Result, bail};
+use serde_json::{Value, json};
+
+use crate::{
+    cli::Cli,
+    formats::{DecodedShare, ShareKeys, decode_shares},
+    output::JSON_SCHEMA_VERSION,
+};
+
+/// The shares present from one group of a split.
+#[derive(Debug)]
+pub struct GroupStatus {
+    pub index: usize,
+    /// The member threshold, known only if a share of the group is present.
+    pub member_threshold: Option<usize>,
+    /// The distinct member indexes present, in ascending order.
+    pub members: Vec<usize>,
+}
+
+impl GroupStatus {
+    /// The number of further members needed to reach the member threshold,
+    /// if known.
+    pub fn needed(&self) -> Option<usize> {
+        self.member_threshold
+            .map(|threshold| threshold.saturating_sub(self.members.len()))
+    }
+
+    pub fn is_satisfied(&self) -> bool { self.needed() == Some(0) }
+
+    pub fn describe(&self) -> String {
+        let Some(threshold) = self.member_threshold else {
+            return format!("Group {}: no shares", self.index + 1);
+        };
+        let members = self
+            .members
+            .iter()
+            .map(|member| (member + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let status = match self.needed() {
+            Some(0) => "satisfied".to_string(),
+            Some(1) => "need 1 more share".to_string(),
+            Some(n) => format!("need {} more shares", n),
+            None => unreachable!(),
+        };
+        format!(
+            "Group {}: member{} {} present, {} required ({})",
+            self.index + 1,
+            if self.members.len() == 1 { "" } else { "s" },
+            members,
+            threshold,
+            status
+        )
+    }
+}
+
+/// A share that can't contribute to recovering its split.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShareProblem {
+    /// An identical copy of an earlier share.
+    Duplicate { line: usize, of_line: usize },
+    /// The same group and member as an earlier share, but different content,
+    /// so one of them is corrupted.
+    Conflict { line: usize, with_line: usize },
+    /// Thresholds that disagree with the earlier shares of the split, so
+    /// either it or they are corrupted.
+    Inconsistent { line: usize },
+}
+
+impl ShareProblem {
+    pub fn describe(&self) -> String {
+        match self {
+            ShareProblem::Duplicate { line, of_line } => {
+                format!("Line {} duplicates line {}.", line, of_line)
+            }
+            ShareProblem::Conflict { line, with_line } => format!(
+                "Line {} is the same member as line {} but differs from it, so one of them is corrupted.",
+                line, with_line
+            ),
+            ShareProblem::Inconsistent { line } => format!(
+                "Line {} has thresholds that disagree with the other shares of its split, so it may be corrupted.",
+                line
+            ),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            ShareProblem::Duplicate { line, of_line } => {
+                json!({ "problem": "duplicate", "line": line, "of": of_line })
+            }
+            ShareProblem::Conflict { line, with_line } => {
+                json!({ "problem": "conflict", "line": line, "with": with_line })
+            }
+            ShareProblem::Inconsistent { line } => {
+                json!({ "problem": "inconsistent", "line": line })
+            }
+        }
+    }
+}
+
+/// The shares present from one split, i.e., one run of SSKR with a single
+/// identifier.
+#[derive(Debug)]
+pub struct SplitStatus {
+    pub identifier: u16,
+    pub group_threshold: usize,
+    pub group_count: usize,
+    pub groups: Vec<GroupStatus>,
+    /// The positions in the analyzed shares of those that can be combined.
+    pub usable: Vec<usize>,
+    pub problems: Vec<ShareProblem>,
+}
+
+impl SplitStatus {
+    pub fn identifier_hex(&self) -> String {
+        hex::encode(self.identifier.to_be_bytes())
+    }
+
+    pub fn satisfied_groups(&self) -> usize {
+        self.groups.iter().filter(|g| g.is_satisfied()).count()
+    }
+
+    pub fn is_recoverable(&self) -> bool {
+        self.satisfied_groups() >= self.group_threshold
+    }
+
+    /// Explains what is still needed to recover the split.
+    pub fn diagnose(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Split {} needs {} of its {} groups, and {} {} satisfied:",
+            self.identifier_hex(),
+            self.group_threshold,
+            self.group_count,
+            self.satisfied_groups(),
+            if self.satisfied_groups() == 1 {
+                "is"
+            } else {
+                "are"
+            }
+        )];
+        for group in &self.groups {
+            let line = match group.needed() {
+                Some(0) => format!("group {} is satisfied", group.index + 1),
+                Some(1) => {
+                    format!("need 1 more share from group {}", group.index + 1)
+                }
+                Some(n) => format!(
+                    "need {} more shares from group {}",
+                    n,
+                    group.index + 1
+                ),
+                None => format!(
+                    "no shares from group {}, so its threshold is unknown",
+                    group.index + 1
+                ),
+            };
+            lines.push(format!("  {}", line));
+        }
+        lines.extend(
+            self.problems.iter().map(|p| format!("  {}", p.describe())),
+        );
+        lines
+    }
+
+    pub fn describe(&self) -> String {
+        let mut lines = vec![format!(
+            "Split {}: {} of {} group{} required, {} satisfied ({})",
+            self.identifier_hex(),
+            self.group_threshold,
+            self.group_count,
+            if self.group_count == 1 { "" } else { "s" },
+            self.satisfied_groups(),
+            if self.is_recoverable() {
+                "recoverable"
+            } else {
+                "not recoverable"
+            }
+        )];
+        lines.extend(self.groups.iter().map(|g| format!("  {}", g.describe())));
+        lines.extend(
+            self.problems.iter().map(|p| format!("  {}", p.describe())),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Groups the shares, each paired with its line number, by the split they
+/// came from, in order of first appearance.
+pub fn analyze_shares(shares: &[(usize, &DecodedShare)]) -> Vec<SplitStatus> {
+    let mut splits: Vec<SplitStatus> = Vec::new();
+    for (position, (line, decoded)) in shares.iter().enumerate() {
+        let share = &decoded.share;
+        let split = match splits
+            .iter_mut()
+            .find(|split| split.identifier == share.identifier())
+        {
+            Some(split) => split,
+            None => {
+                splits.push(SplitStatus {
+                    identifier: share.identifier(),
+                    group_threshold: share.group_threshold(),
+                    group_count: share.group_count(),
+                    groups: (0..share.group_count())
+                        .map(|index| GroupStatus {
+                            index,
+                            member_threshold: None,
+                            members: Vec::new(),
+                        })
+                        .collect(),
+                    usable: Vec::new(),
+                    problems: Vec::new(),
+                });
+                splits.last_mut().unwrap()
+            }
+        };
+
+        if share.group_threshold() != split.group_threshold
+            || share.group_count() != split.group_count
+            || split.groups[share.group_index()]
+                .member_threshold
+                .is_some_and(|t| t != share.member_threshold())
+        {
+            split
+                .problems
+                .push(ShareProblem::Inconsistent { line: *line });
+            continue;
+        }
+
+        let earlier = split.usable.iter().map(|p| shares[*p]).find(|(_, d)| {
+            d.share.group_index() == share.group_index()
+                && d.share.member_index() == share.member_index()
+        });
+        if let Some((earlier_line, earlier)) = earlier {
+            if earlier.share.as_bytes() == share.as_bytes() {
+                split.problems.push(ShareProblem::Duplicate {
+                    line: *line,
+                    of_line: earlier_line,
+                });
+            } else {
+                split.problems.push(ShareProblem::Conflict {
+                    line: *line,
+                    with_line: earlier_line,
+                });
+            }
+            continue;
+        }
+
+        let group = &mut split.groups[share.group_index()];
+        group.member_threshold = Some(share.member_threshold());
+        group.members.push(share.member_index());
+        group.members.sort();
+        split.usable.push(position);
+    }
+    splits
+}
+
+fn inspect_text(
+    decoded: &[(usize, Result<DecodedShare>)],
+    splits: &[SplitStatus],
+) -> String {
+    let mut lines = Vec::new();
+    for (line, result) in decoded {
+        match result {
+            Ok(decoded) => {
+                let share = &decoded.share;
+                lines.push(format!(
+                    "Line {}: split {}, group {} of {} ({} groups required), member {} ({} members required), {}",
+                    line,
+                    share.identifier_hex(),
+                    share.group_index() + 1,
+                    share.group_count(),
+                    share.group_threshold(),
+                    share.member_index() + 1,
+                    share.member_threshold(),
+                    decoded.encoding.name()
+                ));
+            }
+            Err(error) => {
+                lines.push(format!("Line {} ignored: {}", line, error));
+            }
+        }
+    }
+    for split in splits {
+        lines.push(String::new());
+        lines.push(split.describe());
+    }
+    if splits.len() > 1 {
+        lines.push(String::new());
+        lines.push(format!(
+            "Warning: The shares come from {} different splits, which can't be combined with each other.",
+            splits.len()
+        ));
+    }
+    lines.join("\n")
+}
+
+fn inspect_json(
+    decoded: &[(usize, Result<DecodedShare>)],
+    splits: &[SplitStatus],
+) -> Value {
+    let shares: Vec<Value> = decoded
+        .iter()
+        .filter_map(|(line, result)| {
+            let decoded = result.as_ref().ok()?;
+            let share = &decoded.share;
+            Some(json!({
+                "line": line,
+                "identifier": share.identifier_hex(),
+                "groupIndex": share.group_index(),
+                "groupThreshold": share.group_threshold(),
+                "groupCount": share.group_count(),
+                "memberIndex": share.member_index(),
+                "memberThreshold": share.member_threshold(),
+                "sskrFormat": decoded.encoding.name(),
+            }))
+        })
+        .collect();
+    let ignored: Vec<Value> = decoded
+        .iter()
+        .filter_map(|(line, result)| {
+            let error = result.as_ref().err()?;
+            Some(json!({ "line": line, "error": error.to_string() }))
+        })
+        .collect();
+    let splits: Vec<Value> = splits
+        .iter()
+        .map(|split| {
+            json!({
+                "identifier": split.identifier_hex(),
+                "groupThreshold": split.group_threshold,
+                "groupCount": split.group_count,
+                "recoverable": split.is_recoverable(),
+                "groups": split.groups.iter().map(|group| json!({
+                    "groupIndex": group.index,
+                    "memberThreshold": group.member_threshold,
+                    "memberIndexes": group.members,
+                    "satisfied": group.is_satisfied(),
+                })).collect::<Vec<_>>(),
+                "problems": split.problems.iter().map(ShareProblem::to_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    json!({
+        "version": JSON_SCHEMA_VERSION,
+        "shares": shares,
+        "ignored": ignored,
+        "splits": splits,
+    })
+}
+
+/// Reports what each share in the input is, and how the shares relate to
+/// each other, without attempting to recover the secret.
+pub fn run_inspect(state: &Cli) -> Result<()> {
+    let input = state.expect_input()?;
+    let decoded = decode_shares(&input, &ShareKeys::new(state));
+    let shares: Vec<(usize, &DecodedShare)> = decoded
+        .iter()
+        .filter_map(|(line, result)| Some((*line, result.as_ref().ok()?)))
+        .collect();
+    if shares.is_empty() {
+        bail!("No SSKR shares found.");
+    }
+    let splits = analyze_shares(&shares);
+    if state.json {
+        let document = inspect_json(&decoded, &splits);
+        println!("{}", serde_json::to_string_pretty(&document)?);
+    } else {
+        println!("{}", inspect_text(&decoded, &splits));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bc_components::{SSKRShare, sskr_generate};
+    use bc_envelope::prelude::*;
+    use sskr::{GroupSpec, Secret, Spec};
+
+    use super::*;
+
+    fn ur(share: &SSKRShare) -> String {
+        UR::new("sskr", CBOR::to_byte_string(share.as_bytes()))
+            .unwrap()
+            .string()
+    }
+
+    #[test]
+    fn test_analyze_shares() {
+        let spec = Spec::new(
+            2,
+            vec![GroupSpec::new(2, 3).unwrap(), GroupSpec::new(3, 5).unwrap()],
+        )
+        .unwrap();
+        let secret = Secret::new([0u8; 16]).unwrap();
+        let groups = sskr_generate(&spec, &secret).unwrap();
+        let mut corrupted = groups[0][1].as_bytes().to_vec();
+        corrupted[10] ^= 1;
+        let corrupted = SSKRShare::from_data(corrupted);
+        let other = sskr_generate(&spec, &secret).unwrap();
+
+        let input = [
+            ur(&groups[0][0]),
+            ur(&groups[0][1]),
+            ur(&groups[1][4]),
+            ur(&groups[0][0]),
+            ur(&corrupted),
+            ur(&other[1][0]),
+        ]
+        .join("\n");
+        let decoded = decode_shares(&input, &ShareKeys::default());
+        let shares: Vec<(usize, &DecodedShare)> = decoded
+            .iter()
+            .map(|(line, result)| (*line, result.as_ref().unwrap()))
+            .collect();
+        let splits = analyze_shares(&shares);
+        assert_eq!(splits.len(), 2);
+
+        let split = &splits[0];
+        assert_eq!(split.usable, [0, 1, 2]);
+        assert_eq!(split.groups[0].members, [0, 1]);
+        assert!(split.groups[0].is_satisfied());
+        assert_eq!(split.groups[1].needed(), Some(2));
+        assert!(!split.is_recoverable());
+        assert_eq!(
+            split.problems,
+            [
+                ShareProblem::Duplicate {
+                    line: 4,
+                    of_line: 1
+                },
+                ShareProblem::Conflict {
+                    line: 5,
+                    with_line: 2
+                },
+            ]
+        );
+        assert_eq!(
+            split.groups[1].describe(),
+            "Group 2: member 5 present, 3 required (need 2 more shares)"
+        );
+    }
+}
@@ -68,6 +68,34 @@ pub fn digits_to_data(
     Ok(result)
 }
 
+/// The number of ways to choose `k` items from `n`.
+pub fn binomial(n: usize, k: usize) -> u128 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1u128, |acc, i| acc * (n - i) as u128 / (i + 1) as u128)
+}
+
+/// Every way to choose `k` of the indexes `0..n`, in lexicographic order.
+pub fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    let mut combination: Vec<usize> = (0..k).collect();
+    if k > n {
+        return result;
+    }
+    loop {
+        result.push(combination.clone());
+        // Find the rightmost index that can still be incremented.
+        let Some(i) = (0..k).rev().find(|&i| combination[i] < n - k + i) else {
+            return result;
+        };
+        combination[i] += 1;
+        for j in i + 1..k {
+            combination[j] = combination[j - 1] + 1;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,4 +137,17 @@ mod tests {
         let data = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
         assert_eq!(digits_to_data("0123456789", 0, 9).unwrap(), data);
     }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(
+            combinations(4, 2),
+            [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]]
+        );
+        assert_eq!(combinations(3, 0), [Vec::<usize>::new()]);
+        assert!(combinations(2, 3).is_empty());
+        assert_eq!(binomial(4, 2), 6);
+        assert_eq!(binomial(16, 8), combinations(16, 8).len() as u128);
+        assert_eq!(binomial(2, 3), 0);
+    }
 }
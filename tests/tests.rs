@@ -116,6 +116,8 @@ fn test_formats() -> Result<()> {
 
 #[test]
 fn test_envelope() -> Result<()> {
+    bc_envelope::register_tags();
+
     let ur_string = "ur:envelope/lptpsogdnteelblrcygldwvarflojtcywyjytpdkoyadcsspoyaatpsojoghisinjkcxinjkcxjyisihcxjtjljyihoybdtpsoisguihihieglhsjnihoybetpsosecyiyjzvsayehspswda";
     assert_eq!(
         run_cli(&[
@@ -264,3 +266,1652 @@ fn test_multipart() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_auto() -> Result<()> {
+    let hex = "9d347f841a4e2ce6bc886e1aee74d824";
+    for out in ["bip39", "btw", "btwm", "btwu", "envelope", "seed"] {
+        let encoded = run_cli(&["--in", "hex", "--out", out, hex])?;
+        run_cli_expect(&["--in", "auto", &encoded], hex)?;
+    }
+
+    for sskr_format in ["envelope", "btw", "btwm", "btwu", "ur"] {
+        let shares = run_cli(&[
+            "--in",
+            "hex",
+            "--out",
+            "sskr",
+            "--sskr-format",
+            sskr_format,
+            "--groups",
+            "2-of-3",
+            "--",
+            hex,
+        ])?;
+        let selected: Vec<&str> = shares.lines().take(2).collect();
+        run_cli_expect_stdin(&["--in", "auto"], hex, &selected.join("\n"))?;
+    }
+
+    let parts = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "multipart",
+        "--max-fragment-len",
+        "10",
+        hex,
+    ])?;
+    run_cli_expect_stdin(&["--in", "auto"], hex, &parts)?;
+
+    // Valid as both hex and Bytewords Minimal.
+    assert!(run_cli(&["--in", "auto", "cedacabaecdebebdce"]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_multiple_outputs() -> Result<()> {
+    #[rustfmt::skip]
+    let expected = indoc! {"
+        # hex
+        9d347f841a4e2ce6bc886e1aee74d824
+
+        # bip39
+        outdoor physical three cruel tissue infant vendor assist brain inhale current emotion
+
+        # btwm
+        nteelblrcygldwvarflojtcywyjytpdklddyoymk
+    "};
+    run_cli_expect(
+        &[
+            "--deterministic",
+            "TEST",
+            "--out",
+            "hex,bip39",
+            "--out",
+            "btwm",
+        ],
+        expected,
+    )?;
+
+    // Repeated formats are only output once.
+    run_cli_expect(
+        &["--deterministic", "TEST", "--out", "hex", "--out", "hex"],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    )?;
+
+    // Every section comes from the same seed.
+    let kit = run_cli(&["--out", "hex,envelope"])?;
+    let sections: Vec<&str> = kit.split("\n\n").collect();
+    let hex = sections[0].strip_prefix("# hex\n").unwrap();
+    let envelope = sections[1].strip_prefix("# envelope\n").unwrap();
+    run_cli_expect(&["--in", "envelope", envelope], hex)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_json() -> Result<()> {
+    let document: serde_json::Value = serde_json::from_str(&run_cli(&[
+        "--deterministic",
+        "TEST",
+        "--json",
+        "--name",
+        "SeedName",
+        "--date",
+        "2024-06-15",
+        "--out",
+        "hex,sskr",
+        "--sskr-format",
+        "btwm",
+        "--groups",
+        "1-of-2",
+    ])?)?;
+    assert_eq!(document["version"], 1);
+    assert_eq!(
+        document["seed"],
+        serde_json::json!({
+            "name": "SeedName",
+            "note": null,
+            "date": "2024-06-15",
+        })
+    );
+    assert_eq!(
+        document["outputs"][0],
+        serde_json::json!({
+            "format": "hex",
+            "value": "9d347f841a4e2ce6bc886e1aee74d824",
+        })
+    );
+
+    let sskr = &document["outputs"][1];
+    assert_eq!(sskr["format"], "sskr");
+    assert_eq!(sskr["sskrFormat"], "btwm");
+    let shares = sskr["shares"].as_array().unwrap();
+    assert_eq!(shares.len(), 2);
+    for (i, share) in shares.iter().enumerate() {
+        assert_eq!(share["identifier"], shares[0]["identifier"]);
+        assert_eq!(share["groupIndex"], 0);
+        assert_eq!(share["groupThreshold"], 1);
+        assert_eq!(share["groupCount"], 1);
+        assert_eq!(share["memberIndex"], i);
+        assert_eq!(share["memberThreshold"], 1);
+        run_cli_expect(
+            &["--in", "sskr", share["share"].as_str().unwrap()],
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        )?;
+    }
+
+    let document: serde_json::Value = serde_json::from_str(&run_cli(&[
+        "--json",
+        "--out",
+        "multipart",
+        "--max-fragment-len",
+        "10",
+    ])?)?;
+    let parts = document["outputs"][0]["parts"].as_array().unwrap();
+    for (i, part) in parts.iter().enumerate() {
+        let seq_num = part["seqNum"].as_u64().unwrap();
+        let seq_len = part["seqLen"].as_u64().unwrap();
+        assert_eq!(seq_num, i as u64 + 1);
+        assert!(
+            part["part"]
+                .as_str()
+                .unwrap()
+                .starts_with(&format!("ur:envelope/{}-{}/", seq_num, seq_len))
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_batch() -> Result<()> {
+    #[rustfmt::skip]
+    let input = indoc! {r#"
+        9d347f841a4e2ce6bc886e1aee74d824
+
+        not hex
+        {"input": "00112233445566778899aabbccddeeff", "name": "SeedName"}
+    "#};
+
+    // Failed records are reported, and the rest of the batch still runs.
+    let output = run_cli_raw_stdin(
+        &["--batch", "-", "--in", "hex", "--out", "btwm"],
+        input,
+    );
+    assert!(output.is_err());
+
+    let output = assert_cmd::cargo::cargo_bin_cmd!("seedtool")
+        .args(["--batch", "-", "--in", "hex", "--out", "btwm", "--json"])
+        .write_stdin(input)
+        .assert()
+        .failure();
+    let stdout = String::from_utf8(output.get_output().stdout.to_vec())?;
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["record"], 1);
+    assert_eq!(
+        lines[0]["outputs"][0]["value"],
+        "nteelblrcygldwvarflojtcywyjytpdklddyoymk"
+    );
+    assert_eq!(lines[1]["record"], 3);
+    assert!(lines[1]["error"].is_string());
+    assert_eq!(lines[2]["record"], 4);
+    assert_eq!(lines[2]["seed"]["name"], "SeedName");
+    assert_eq!(
+        lines[2]["outputs"][0]["value"],
+        "aebycpeofygoiyktlonlpkrksfutwyzmlratkpnd"
+    );
+
+    let seeds = run_cli(&["--deterministic", "TEST", "--batch-count", "3"])?;
+    let seeds: Vec<&str> = seeds.lines().collect();
+    assert_eq!(seeds.len(), 3);
+    assert_eq!(seeds[0], "9d347f841a4e2ce6bc886e1aee74d824");
+    assert_ne!(seeds[1], seeds[2]);
+
+    assert!(run_cli(&["--in", "hex", "--batch-count", "3"]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_sskr_metadata_overrides() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--name",
+        "SeedName",
+        "--groups",
+        "1-of-1",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let envelope = run_cli(&["--in", "sskr", "--out", "envelope", &shares])?;
+    let envelope = Envelope::from_ur_string(envelope)?;
+    let name: String =
+        envelope.extract_object_for_predicate(known_values::NAME)?;
+    assert_eq!(name, "SeedName");
+
+    Ok(())
+}
+
+#[test]
+fn test_bip39_assist() -> Result<()> {
+    // Words may be abbreviated to their first four letters.
+    run_cli_expect(
+        &[
+            "--in",
+            "bip39",
+            "--bip39-assist",
+            "matr pull accu apar horn chat next rifl rese arti unti eye",
+        ],
+        "8935a8068526d84da555cdb741a3b8a8",
+    )?;
+
+    // Without the flag, abbreviations are rejected.
+    assert!(
+        run_cli(&[
+            "--in",
+            "bip39",
+            "matr pull accu apar horn chat next rifl rese arti unti eye",
+        ])
+        .is_err()
+    );
+
+    let err = run_cli(&[
+        "--in",
+        "bip39",
+        "--bip39-assist",
+        "matrix pull accuse apart horse chat next rifle resemble artist until eye",
+    ])
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains(r#"Word 5: \"horse\" -> \"horn\""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_bip39_recover() -> Result<()> {
+    let partial = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon ?";
+    let about = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    // Every candidate with a valid checksum is output.
+    let candidates = run_cli_stdin(
+        &["--in", "bip39", "--bip39-recover", "--out", "bip39"],
+        partial,
+    )?;
+    assert_eq!(candidates.lines().count(), 128);
+    assert!(candidates.lines().any(|line| line == about));
+
+    // Filtering by master key fingerprint.
+    run_cli_expect_stdin(
+        &[
+            "--in",
+            "bip39",
+            "--bip39-recover",
+            "--out",
+            "bip39",
+            "--fingerprint",
+            "73c5da0a",
+        ],
+        about,
+        partial,
+    )?;
+
+    // Filtering by first receive address, with a partially known word.
+    run_cli_expect_stdin(
+        &[
+            "--in",
+            "bip39",
+            "--bip39-recover",
+            "--out",
+            "bip39",
+            "--address",
+            "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf",
+        ],
+        about,
+        &partial.replace('?', "ab?"),
+    )?;
+
+    // Without placeholders, pairs of words are swapped.
+    let candidates = run_cli_stdin(
+        &["--in", "bip39", "--bip39-recover"],
+        "matrix accuse pull apart horn chat next rifle resemble artist until eye",
+    )?;
+    assert!(
+        candidates
+            .lines()
+            .any(|line| line == "8935a8068526d84da555cdb741a3b8a8")
+    );
+
+    assert!(
+        run_cli_stdin(
+            &[
+                "--in",
+                "bip39",
+                "--bip39-recover",
+                "--fingerprint",
+                "00000000"
+            ],
+            partial,
+        )
+        .is_err()
+    );
+    assert!(
+        run_cli_stdin(&["--in", "hex", "--bip39-recover"], partial).is_err()
+    );
+
+    // A mistyped address is rejected before searching.
+    let error = run_cli_stdin(
+        &[
+            "--in",
+            "bip39",
+            "--bip39-recover",
+            "--address",
+            "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgg",
+        ],
+        partial,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("Invalid address"));
+
+    // The passphrase only applies to recovery.
+    assert!(
+        run_cli_stdin(&["--in", "bip39", "--passphrase=TREZOR"], about)
+            .is_err()
+    );
+
+    // A passphrase is attached with "=", so the mnemonic after it is never
+    // taken for the passphrase.
+    let recover = |passphrase| {
+        run_cli(&[
+            "--in",
+            "bip39",
+            "--bip39-recover",
+            "--out",
+            "bip39",
+            "--fingerprint",
+            "73c5da0a",
+            passphrase,
+            partial,
+        ])
+    };
+    assert_eq!(recover("--passphrase=")?, about);
+    assert!(recover("--passphrase=TREZOR").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_bip39_last_word() -> Result<()> {
+    let words = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+
+    // Every entropy bit of the last word given.
+    run_cli_expect_stdin(
+        &[
+            "--in",
+            "bip39",
+            "--bip39-last-word",
+            "--last-word-bits",
+            "0000000",
+            "--out",
+            "bip39",
+        ],
+        &format!("{} about", words),
+        words,
+    )?;
+
+    // A deterministic choice from the valid final words.
+    let args = [
+        "--in",
+        "bip39",
+        "--bip39-last-word",
+        "--deterministic",
+        "TEST",
+        "--out",
+        "bip39",
+    ];
+    let mnemonic = run_cli_stdin(&args, words)?;
+    assert!(mnemonic.starts_with(words));
+    run_cli_expect_stdin(&args, &mnemonic, words)?;
+    assert!(run_cli_stdin(&["--in", "bip39"], &mnemonic).is_ok());
+
+    assert!(
+        run_cli_stdin(
+            &[
+                "--in",
+                "bip39",
+                "--bip39-last-word",
+                "--last-word-bits",
+                "2"
+            ],
+            words,
+        )
+        .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sskr_inspect() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--groups",
+        "2-of-3",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let shares: Vec<&str> = shares.lines().collect();
+    let input = ["a comment", shares[0], shares[2], shares[0]].join("\n");
+
+    let report = run_cli_stdin(&["--in", "sskr", "--sskr-inspect"], &input)?;
+    assert!(report.contains("Line 1 ignored"));
+    assert!(
+        report
+            .contains("Group 1: members 1, 3 present, 2 required (satisfied)")
+    );
+    assert!(report.contains("Line 4 duplicates line 2."));
+
+    let report: serde_json::Value = serde_json::from_str(&run_cli_stdin(
+        &["--in", "sskr", "--sskr-inspect", "--json"],
+        &input,
+    )?)?;
+    assert_eq!(report["shares"].as_array().unwrap().len(), 3);
+    assert_eq!(report["ignored"][0]["line"], 1);
+    assert_eq!(report["splits"][0]["recoverable"], true);
+    assert_eq!(report["splits"][0]["problems"][0]["problem"], "duplicate");
+
+    assert!(run_cli_stdin(&["--in", "sskr", "--sskr-inspect"], "foo").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_sskr_reshare() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--name",
+        "SeedName",
+        "--groups",
+        "2-of-3",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let old_shares: Vec<&str> = shares.lines().take(2).collect();
+    let old_shares = old_shares.join("\n");
+
+    let new_shares = run_cli_stdin(
+        &["--in", "sskr", "--sskr-reshare", "--groups", "3-of-5"],
+        &old_shares,
+    )?;
+    assert_eq!(new_shares.lines().count(), 5);
+
+    // The new split has a new identifier.
+    let identifier = |shares: &str| -> Result<serde_json::Value> {
+        let report: serde_json::Value = serde_json::from_str(&run_cli_stdin(
+            &["--in", "sskr", "--sskr-inspect", "--json"],
+            shares,
+        )?)?;
+        Ok(report["splits"][0]["identifier"].clone())
+    };
+    assert_ne!(identifier(&old_shares)?, identifier(&new_shares)?);
+
+    // The seed and its metadata survive.
+    let three: Vec<&str> = new_shares.lines().take(3).collect();
+    let envelope = run_cli_stdin(
+        &["--in", "sskr", "--out", "envelope"],
+        &three.join("\n"),
+    )?;
+    let envelope = Envelope::from_ur_string(envelope)?;
+    let name: String =
+        envelope.extract_object_for_predicate(known_values::NAME)?;
+    assert_eq!(name, "SeedName");
+    run_cli_expect_stdin(
+        &["--in", "sskr"],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &three.join("\n"),
+    )?;
+
+    // Too few shares from the new split.
+    assert!(run_cli_stdin(&["--in", "sskr"], &three[..2].join("\n")).is_err());
+
+    assert!(
+        run_cli_stdin(
+            &["--in", "hex", "--sskr-reshare", "--groups", "1-of-1"],
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        )
+        .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sskr_verify() -> Result<()> {
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--sskr-verify",
+        "--group-threshold",
+        "2",
+        "--groups",
+        "2-of-3",
+        "3-of-5",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    assert_eq!(shares.lines().count(), 8);
+
+    // Too many sets of shares to test, which is found before any of them
+    // are built.
+    for (threshold, group_count) in [("2", 2), ("8", 16)] {
+        let mut args = vec![
+            "--in",
+            "hex",
+            "--out",
+            "sskr",
+            "--sskr-verify",
+            "--group-threshold",
+            threshold,
+        ];
+        for _ in 0..group_count {
+            args.extend(["--groups", "8-of-16"]);
+        }
+        args.extend(["--", "9d347f841a4e2ce6bc886e1aee74d824"]);
+        let error = run_cli(&args).unwrap_err();
+        assert!(error.to_string().contains("too many sets of shares"));
+    }
+
+    // Without SSKR output there is nothing to verify.
+    assert!(
+        run_cli(&[
+            "--in",
+            "hex",
+            "--out",
+            "hex",
+            "--sskr-verify",
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        ])
+        .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sskr_manifest() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let temp_dir = tempfile::tempdir()?;
+    let manifest = temp_dir.path().join("shares.json");
+    std::fs::write(
+        &manifest,
+        indoc! {r#"
+            {
+                "instructions": "Combine with one other share.",
+                "groups": [
+                    {
+                        "label": "Family",
+                        "members": [
+                            { "custodian": "Alice", "contact": "alice@example.com" },
+                            { "custodian": "Bob" },
+                            { "custodian": "Carol" }
+                        ]
+                    }
+                ]
+            }
+        "#},
+    )?;
+    let manifest = manifest.to_str().unwrap();
+
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--groups",
+        "2-of-3",
+        "--sskr-manifest",
+        manifest,
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let shares: Vec<&str> = shares.lines().collect();
+    for (share, custodian) in shares.iter().zip(["Alice", "Bob", "Carol"]) {
+        let envelope = Envelope::from_ur_string(*share)?;
+        let actual: String =
+            envelope.extract_object_for_predicate("custodian")?;
+        assert_eq!(actual, custodian);
+        let group: String = envelope.extract_object_for_predicate("group")?;
+        assert_eq!(group, "Family");
+        let instructions: String =
+            envelope.extract_object_for_predicate("instructions")?;
+        assert_eq!(instructions, "Combine with one other share.");
+    }
+    let first = Envelope::from_ur_string(shares[0])?;
+    let contact: String = first.extract_object_for_predicate("contact")?;
+    assert_eq!(contact, "alice@example.com");
+
+    // The labelled shares still recover the seed.
+    run_cli_expect_stdin(
+        &["--in", "sskr"],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &shares[1..].join("\n"),
+    )?;
+
+    // The manifest must match the spec, and only applies to envelopes.
+    assert!(
+        run_cli(&[
+            "--in",
+            "hex",
+            "--out",
+            "sskr",
+            "--groups",
+            "2-of-4",
+            "--sskr-manifest",
+            manifest,
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        ])
+        .is_err()
+    );
+    assert!(
+        run_cli(&[
+            "--in",
+            "hex",
+            "--out",
+            "sskr",
+            "--sskr-format",
+            "btwm",
+            "--groups",
+            "2-of-3",
+            "--sskr-manifest",
+            manifest,
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        ])
+        .is_err()
+    );
+
+    // Nor without SSKR output.
+    assert!(
+        run_cli(&[
+            "--in",
+            "hex",
+            "--out",
+            "hex",
+            "--sskr-manifest",
+            manifest,
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        ])
+        .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sskr_recipients() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let alice_public = "ur:crypto-pubkeys/lftanshfhdcxhkottkhhwkueuyspkplnesceehpletluhygmolehykvogsrfmsrfsslosrlsvytetansgrhdcxmtytmkkogrwyrekibwnbhtjerpwdzmbwdirtmenyweetleadrpdlfydygtdmrsfeiymkjnzt";
+    let alice_private = "ur:crypto-prvkeys/lftansgohdcxldjomofhwtpywyvawygalgvavehtfheowncwiyislurdcpylcwjzgrythhlylytbtansgehdcxvwluetghdrksjnvyksynndndylgtskltgdqzamvspdrkstcpvenlsssbttreghuonnkgfzgt";
+    let bob_public = "ur:crypto-pubkeys/lftanshfhdcxpeahwmjefxytjsztmncajojzfsvokenbgrgycnlsidcmguwfurvorogdvyktoluetansgrhdcxmwfyadsftkrscygmdyzsrdpybkryskbwbkhnbttaskdpfxrtdthedytldkvsvsbevladwngm";
+    let bob_private_base = "ur:crypto-prvkey-base/hdcxaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaomuwpasbw";
+
+    let temp_dir = tempfile::tempdir()?;
+    let manifest = temp_dir.path().join("shares.json");
+    std::fs::write(
+        &manifest,
+        serde_json::json!({
+            "groups": [{
+                "members": [
+                    { "custodian": "Alice", "recipient": alice_public },
+                    { "custodian": "Bob", "recipient": bob_public },
+                    { "custodian": "Carol" },
+                ]
+            }]
+        })
+        .to_string(),
+    )?;
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--groups",
+        "2-of-3",
+        "--sskr-manifest",
+        manifest.to_str().unwrap(),
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let shares: Vec<&str> = shares.lines().collect();
+
+    // The encrypted shares still show who holds them.
+    let alice_share = Envelope::from_ur_string(shares[0])?;
+    let custodian: String =
+        alice_share.extract_object_for_predicate("custodian")?;
+    assert_eq!(custodian, "Alice");
+    assert_eq!(alice_share.recipients()?.len(), 1);
+
+    let alice_and_bob = shares[..2].join("\n");
+    assert!(run_cli_stdin(&["--in", "sskr"], &alice_and_bob).is_err());
+    assert!(
+        run_cli_stdin(
+            &["--in", "sskr", "--sskr-key", alice_private],
+            &alice_and_bob
+        )
+        .is_err()
+    );
+    run_cli_expect_stdin(
+        &[
+            "--in",
+            "sskr",
+            "--sskr-key",
+            alice_private,
+            "--sskr-key",
+            bob_private_base,
+        ],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &alice_and_bob,
+    )?;
+    run_cli_expect_stdin(
+        &["--in", "sskr", "--sskr-key", bob_private_base],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &shares[1..].join("\n"),
+    )?;
+
+    // A key may be read from a file instead.
+    let bob_key_file = temp_dir.path().join("bob.ur");
+    std::fs::write(&bob_key_file, format!("{}\n", bob_private_base))?;
+    run_cli_expect_stdin(
+        &["--in", "sskr", "--sskr-key", bob_key_file.to_str().unwrap()],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &shares[1..].join("\n"),
+    )?;
+    let missing_file = temp_dir.path().join("missing.ur");
+    assert!(
+        run_cli_stdin(
+            &["--in", "sskr", "--sskr-key", missing_file.to_str().unwrap()],
+            &alice_and_bob,
+        )
+        .is_err()
+    );
+
+    // Other outputs in the same run aren't encrypted, which is warned about.
+    let output = assert_cmd::cargo::cargo_bin_cmd!("seedtool")
+        .args([
+            "--in",
+            "hex",
+            "--out",
+            "sskr,hex",
+            "--groups",
+            "2-of-3",
+            "--sskr-manifest",
+            manifest.to_str().unwrap(),
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        ])
+        .assert()
+        .success();
+    let stderr = String::from_utf8(output.get_output().stderr.to_vec())?;
+    assert!(stderr.contains("The other outputs are not encrypted."));
+
+    Ok(())
+}
+
+#[test]
+fn test_out_dir() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let temp_dir = tempfile::tempdir()?;
+    let dir = temp_dir.path().join("shares");
+    let dir_str = dir.to_str().unwrap();
+    let files = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr,multipart",
+        "--groups",
+        "2-of-3",
+        "--max-fragment-len",
+        "20",
+        "--out-dir",
+        dir_str,
+        "--digests",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let names: Vec<String> = files
+        .lines()
+        .map(|path| {
+            std::path::Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "sskr-group-1-member-1.txt",
+            "sskr-group-1-member-2.txt",
+            "sskr-group-1-member-3.txt",
+            "multipart-part-1.txt",
+            "multipart-part-2.txt",
+            "digests.json",
+        ]
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(dir.join(&names[0]))?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let read = |name: &str| std::fs::read_to_string(dir.join(name));
+    run_cli_expect_stdin(
+        &["--in", "sskr"],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &(read(&names[0])? + &read(&names[2])?),
+    )?;
+    run_cli_expect_stdin(
+        &["--in", "multipart"],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &(read(&names[3])? + &read(&names[4])?),
+    )?;
+
+    // The digests describe the files without revealing them.
+    let digests: serde_json::Value =
+        serde_json::from_str(&read("digests.json")?)?;
+    let first = &digests["files"][0];
+    assert_eq!(first["file"], names[0]);
+    assert_eq!(
+        first["sha256"],
+        hex::encode(bc_crypto::sha256(read(&names[0])?.as_bytes()))
+    );
+    assert!(!read("digests.json")?.contains("ur:"));
+
+    // Existing files are never overwritten.
+    assert!(
+        run_cli(&[
+            "--in",
+            "hex",
+            "--out",
+            "sskr",
+            "--groups",
+            "2-of-3",
+            "--out-dir",
+            dir_str,
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        ])
+        .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sskr_distribution() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let signer = "ur:crypto-prvkey-base/hdcxaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaoaomuwpasbw";
+    let verifier = "ur:crypto-pubkeys/lftanshfhdcxpeahwmjefxytjsztmncajojzfsvokenbgrgycnlsidcmguwfurvorogdvyktoluetansgrhdcxmwfyadsftkrscygmdyzsrdpybkryskbwbkhnbttaskdpfxrtdthedytldkvsvsbevladwngm";
+    let other_verifier = "ur:crypto-pubkeys/lftanshfhdcxhkottkhhwkueuyspkplnesceehpletluhygmolehykvogsrfmsrfsslosrlsvytetansgrhdcxmtytmkkogrwyrekibwnbhtjerpwdzmbwdirtmenyweetleadrpdlfydygtdmrsfeiymkjnzt";
+
+    let temp_dir = tempfile::tempdir()?;
+    let path = temp_dir.path().join("distribution.ur");
+    let path = path.to_str().unwrap();
+    let signer_file = temp_dir.path().join("signer.ur");
+    std::fs::write(&signer_file, format!("{}\n", signer))?;
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--sskr-format",
+        "btwm",
+        "--groups",
+        "2-of-3",
+        "--sskr-distribution",
+        path,
+        "--sskr-distribution-signer",
+        signer_file.to_str().unwrap(),
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let shares: Vec<&str> = shares.lines().collect();
+
+    // The manifest reveals neither the seed nor the shares.
+    let manifest = std::fs::read_to_string(path)?;
+    let envelope = Envelope::from_ur_string(manifest.trim())?
+        .verify(&bc_components::PublicKeys::from_ur_string(verifier)?)?;
+    assert!(envelope.subject().is_elided());
+    assert_eq!(envelope.objects_for_predicate("share").len(), 3);
+    let identifier: String =
+        envelope.extract_object_for_predicate("sskrIdentifier")?;
+    assert_eq!(identifier.len(), 4);
+
+    run_cli_expect_stdin(
+        &[
+            "--in",
+            "sskr",
+            "--sskr-distribution-check",
+            path,
+            "--sskr-distribution-verifier",
+            verifier,
+        ],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &shares[..2].join("\n"),
+    )?;
+    assert!(
+        run_cli_stdin(
+            &[
+                "--in",
+                "sskr",
+                "--sskr-distribution-check",
+                path,
+                "--sskr-distribution-verifier",
+                other_verifier,
+            ],
+            &shares[..2].join("\n"),
+        )
+        .is_err()
+    );
+
+    // Shares from another split don't match.
+    let other_shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--sskr-format",
+        "btwm",
+        "--groups",
+        "2-of-3",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let other_shares: Vec<&str> = other_shares.lines().collect();
+    assert!(
+        run_cli_stdin(
+            &["--in", "sskr", "--sskr-distribution-check", path],
+            &other_shares[..2].join("\n"),
+        )
+        .is_err()
+    );
+
+    // Without SSKR output, no distribution manifest would be written.
+    let unwritten = temp_dir.path().join("unwritten.ur");
+    assert!(
+        run_cli(&[
+            "--out",
+            "hex",
+            "--sskr-distribution",
+            unwritten.to_str().unwrap(),
+        ])
+        .is_err()
+    );
+    assert!(!unwritten.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_sskr_metadata() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--sskr-format",
+        "btw",
+        "--sskr-metadata",
+        "--name",
+        "Vault",
+        "--groups",
+        "2-of-3",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let two: Vec<&str> = shares.lines().take(2).collect();
+    let envelope =
+        run_cli_stdin(&["--in", "sskr", "--out", "envelope"], &two.join("\n"))?;
+    let envelope = Envelope::from_ur_string(envelope)?;
+    let name: String =
+        envelope.extract_object_for_predicate(known_values::NAME)?;
+    assert_eq!(name, "Vault");
+
+    // Too much metadata to fit.
+    assert!(
+        run_cli(&[
+            "--in",
+            "hex",
+            "--out",
+            "sskr",
+            "--sskr-format",
+            "btw",
+            "--sskr-metadata",
+            "--note",
+            "This note is far too long to fit in an SSKR share.",
+            "--groups",
+            "2-of-3",
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        ])
+        .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_legacy_ur() -> Result<()> {
+    let seed_ur = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "seed",
+        "--legacy-ur",
+        "--name",
+        "Alice",
+        "--date",
+        "2021-02-24",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    assert_eq!(
+        seed_ur,
+        "ur:crypto-seed/otadgdnteelblrcygldwvarflojtcywyjytpdkaotpiecffdzsaxihfpjziniaihrnswiort"
+    );
+    run_cli_expect(
+        &["--in", "seed", "--out", "hex", &seed_ur],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    )?;
+    run_cli_expect(
+        &["--in", "auto", "--out", "seed", &seed_ur],
+        "ur:seed/otadgdnteelblrcygldwvarflojtcywyjytpdkaosecyhnecmsaeaxihfpjziniaihbalulreo",
+    )?;
+
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--sskr-format",
+        "ur",
+        "--legacy-ur",
+        "--groups",
+        "2-of-3",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let two: Vec<&str> = shares.lines().take(2).collect();
+    assert!(two.iter().all(|share| share.starts_with("ur:crypto-sskr/")));
+    // As BCR-2020-011 defines it, the share is a bare byte string, untagged.
+    for share in &two {
+        assert!(
+            UR::from_ur_string(*share)?
+                .cbor()
+                .as_byte_string()
+                .is_some()
+        );
+    }
+    run_cli_expect_stdin(
+        &["--in", "sskr", "--out", "hex"],
+        "9d347f841a4e2ce6bc886e1aee74d824",
+        &two.join("\n"),
+    )?;
+
+    // Only the seed and ur shares have legacy types.
+    for args in [
+        &["--out", "hex"][..],
+        &["--out", "seed,hex"][..],
+        &["--out", "sskr", "--sskr-format", "btw"][..],
+        &["--out", "sskr"][..],
+    ] {
+        let mut args = args.to_vec();
+        args.extend_from_slice(&[
+            "--legacy-ur",
+            "--in",
+            "hex",
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d824",
+        ]);
+        assert!(run_cli(&args).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_envelope_password() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let temp_dir = tempfile::tempdir()?;
+    let password_file = temp_dir.path().join("password.txt");
+    let password_file = password_file.to_str().unwrap();
+    std::fs::write(password_file, "correct horse battery staple\n")?;
+    let wrong_password_file = temp_dir.path().join("wrong-password.txt");
+    let wrong_password_file = wrong_password_file.to_str().unwrap();
+    std::fs::write(wrong_password_file, "incorrect horse\n")?;
+
+    for kdf_args in [
+        &["--envelope-kdf", "argon2id"][..],
+        &["--envelope-kdf", "scrypt", "--scrypt-log-n", "12"][..],
+    ] {
+        let mut args = vec![
+            "--in",
+            "hex",
+            "--out",
+            "envelope",
+            "--name",
+            "Alice",
+            "--envelope-password",
+            "--password-file",
+            password_file,
+        ];
+        args.extend_from_slice(kdf_args);
+        args.extend_from_slice(&["--", "9d347f841a4e2ce6bc886e1aee74d824"]);
+        let locked = run_cli(&args)?;
+        let envelope = Envelope::from_ur_string(&locked)?;
+        assert!(envelope.is_locked_with_password());
+        assert!(envelope.subject().is_encrypted());
+
+        run_cli_expect_stdin(
+            &["--in", "envelope", "--password-file", password_file],
+            "9d347f841a4e2ce6bc886e1aee74d824",
+            &locked,
+        )?;
+        assert!(
+            run_cli_stdin(
+                &["--in", "envelope", "--password-file", wrong_password_file],
+                &locked,
+            )
+            .is_err()
+        );
+    }
+
+    // The scrypt parameters only apply to scrypt.
+    assert!(
+        run_cli(&[
+            "--out",
+            "envelope",
+            "--envelope-password",
+            "--password-file",
+            password_file,
+            "--scrypt-log-n",
+            "12",
+        ])
+        .is_err()
+    );
+
+    // scrypt may not be asked for more than 4 GiB of memory.
+    assert!(
+        run_cli(&[
+            "--out",
+            "envelope",
+            "--envelope-password",
+            "--password-file",
+            password_file,
+            "--envelope-kdf",
+            "scrypt",
+            "--scrypt-log-n",
+            "24",
+            "--scrypt-r",
+            "32",
+        ])
+        .is_err()
+    );
+
+    // Only envelope output can be locked.
+    for out in [&["multipart"][..], &["envelope", "hex"][..]] {
+        let mut args =
+            vec!["--envelope-password", "--password-file", password_file];
+        for key in out {
+            args.extend_from_slice(&["--out", key]);
+        }
+        assert!(run_cli(&args).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_signing() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let signing_key = temp_dir.path().join("signing-key.ur");
+    std::fs::write(
+        &signing_key,
+        "ur:signing-private-key/lfaohdcxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxbyaomhte\n",
+    )?;
+    let signing_key = signing_key.to_str().unwrap();
+    let verifying_key = "ur:signing-public-key/lfaohdcxwegadeswdettsaswwdwlaxetmhhkmdhsdthkdifthhiaytenensefgbbpsltemttihlumtvt";
+    let other_verifying_key = "ur:signing-public-key/lfaohdcxsgmupschahcsjojstbkglsstzmbazelyayvswpfedyhghlktdsltmueouytnrnkedpzcsbjz";
+    let seed = "9d347f841a4e2ce6bc886e1aee74d824";
+
+    let signed = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "envelope",
+        "--signing-key",
+        signing_key,
+        "--",
+        seed,
+    ])?;
+    run_cli_expect_stdin(&["--in", "envelope"], seed, &signed)?;
+    run_cli_expect_stdin(
+        &["--in", "envelope", "--verifying-key", verifying_key],
+        seed,
+        &signed,
+    )?;
+    assert!(
+        run_cli_stdin(
+            &["--in", "envelope", "--verifying-key", other_verifying_key],
+            &signed,
+        )
+        .is_err()
+    );
+    let unsigned = run_cli(&["--in", "hex", "--out", "envelope", "--", seed])?;
+    assert!(
+        run_cli_stdin(
+            &["--in", "envelope", "--verifying-key", verifying_key],
+            &unsigned,
+        )
+        .is_err()
+    );
+
+    // Each share envelope is signed.
+    let shares = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "sskr",
+        "--signing-key",
+        signing_key,
+        "--groups",
+        "2-of-3",
+        "--",
+        seed,
+    ])?;
+    let two: Vec<&str> = shares.lines().take(2).collect();
+    run_cli_expect_stdin(
+        &["--in", "sskr", "--verifying-key", verifying_key],
+        seed,
+        &two.join("\n"),
+    )?;
+    run_cli_expect_stdin(&["--in", "auto"], seed, &two.join("\n"))?;
+    assert!(
+        run_cli_stdin(
+            &["--in", "sskr", "--verifying-key", other_verifying_key],
+            &two.join("\n"),
+        )
+        .is_err()
+    );
+
+    // A multipart message carries a signed envelope, and is checked like one.
+    let parts = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "multipart",
+        "--signing-key",
+        signing_key,
+        "--",
+        seed,
+    ])?;
+    run_cli_expect_stdin(
+        &["--in", "multipart", "--verifying-key", verifying_key],
+        seed,
+        &parts,
+    )?;
+    assert!(
+        run_cli_stdin(
+            &["--in", "multipart", "--verifying-key", other_verifying_key],
+            &parts,
+        )
+        .is_err()
+    );
+    let unsigned_parts =
+        run_cli(&["--in", "hex", "--out", "multipart", "--", seed])?;
+    for input in ["multipart", "auto"] {
+        assert!(
+            run_cli_stdin(
+                &["--in", input, "--verifying-key", verifying_key],
+                &unsigned_parts,
+            )
+            .is_err()
+        );
+    }
+
+    // A locked envelope is signed outside the lock.
+    let password_file = temp_dir.path().join("password.txt");
+    let password_file = password_file.to_str().unwrap();
+    std::fs::write(password_file, "correct horse battery staple\n")?;
+    let locked = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "envelope",
+        "--envelope-password",
+        "--envelope-kdf",
+        "scrypt",
+        "--scrypt-log-n",
+        "10",
+        "--password-file",
+        password_file,
+        "--signing-key",
+        signing_key,
+        "--",
+        seed,
+    ])?;
+    run_cli_expect_stdin(
+        &[
+            "--in",
+            "envelope",
+            "--verifying-key",
+            verifying_key,
+            "--password-file",
+            password_file,
+        ],
+        seed,
+        &locked,
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn test_envelope_assertions() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let seed = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "envelope",
+        "--name",
+        "Alice",
+        "--",
+        "9d347f841a4e2ce6bc886e1aee74d824",
+    ])?;
+    let envelope = Envelope::from_ur_string(seed)?
+        .add_assertion("custodian", "Bob")
+        .add_attachment(
+            "attachment data",
+            "com.example",
+            Some("https://example.com/attachment"),
+        );
+    let input = envelope.ur_string();
+
+    // The assertions and attachments are kept, in envelope and SSKR outputs.
+    let output =
+        run_cli_stdin(&["--in", "envelope", "--out", "envelope"], &input)?;
+    assert!(Envelope::from_ur_string(output)?.is_identical_to(&envelope));
+    let shares = run_cli_stdin(
+        &["--in", "envelope", "--out", "sskr", "--groups", "2-of-3"],
+        &input,
+    )?;
+    let two: Vec<&str> = shares.lines().take(2).collect();
+    let output =
+        run_cli_stdin(&["--in", "sskr", "--out", "envelope"], &two.join("\n"))?;
+    assert!(Envelope::from_ur_string(output)?.is_identical_to(&envelope));
+
+    // Overriding the name keeps the other assertions.
+    let output = run_cli_stdin(
+        &["--in", "envelope", "--out", "envelope", "--name", "Carol"],
+        &input,
+    )?;
+    let output = Envelope::from_ur_string(output)?;
+    let name: String =
+        output.extract_object_for_predicate(known_values::NAME)?;
+    assert_eq!(name, "Carol");
+    assert_eq!(output.attachments()?.len(), 1);
+
+    let output = run_cli_stdin(
+        &[
+            "--in",
+            "envelope",
+            "--out",
+            "envelope",
+            "--strip-assertions",
+        ],
+        &input,
+    )?;
+    let output = Envelope::from_ur_string(output)?;
+    assert!(output.attachments()?.is_empty());
+    assert!(output.assertions_with_predicate("custodian").is_empty());
+    let name: String =
+        output.extract_object_for_predicate(known_values::NAME)?;
+    assert_eq!(name, "Alice");
+
+    Ok(())
+}
+
+#[test]
+fn test_add_assertions() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let descriptor = "wpkh([73c5da0a/84'/0'/0']xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)";
+    let metadata_args = [
+        "--assertion",
+        "'dereferenceVia'=https://example.com/seed",
+        "--assertion",
+        "custodian=Bob",
+        "--attachment",
+        "vendor=com.example,conforms-to=https://example.com/attachment,payload=attachment data, with a comma",
+        "--output-descriptor",
+        descriptor,
+    ];
+    let check = |ur: &str| -> Result<()> {
+        let envelope = Envelope::from_ur_string(ur)?;
+        let via: String = envelope
+            .extract_object_for_predicate(known_values::DEREFERENCE_VIA)?;
+        assert_eq!(via, "https://example.com/seed");
+        let custodian: String =
+            envelope.extract_object_for_predicate("custodian")?;
+        assert_eq!(custodian, "Bob");
+        let attachments = envelope.attachments()?;
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].attachment_vendor()?, "com.example");
+        let payload: String =
+            attachments[0].attachment_payload()?.extract_subject()?;
+        assert_eq!(payload, "attachment data, with a comma");
+        let output_descriptor: String = envelope
+            .extract_object_for_predicate(known_values::OUTPUT_DESCRIPTOR)?;
+        assert_eq!(output_descriptor, descriptor);
+        Ok(())
+    };
+
+    let mut args = vec!["--in", "hex", "--out", "envelope"];
+    args.extend_from_slice(&metadata_args);
+    args.extend_from_slice(&["--", "9d347f841a4e2ce6bc886e1aee74d824"]);
+    check(&run_cli(&args)?)?;
+
+    let mut args = vec!["--in", "hex", "--out", "sskr", "--groups", "2-of-3"];
+    args.extend_from_slice(&metadata_args);
+    args.extend_from_slice(&["--", "9d347f841a4e2ce6bc886e1aee74d824"]);
+    let shares = run_cli(&args)?;
+    let two: Vec<&str> = shares.lines().take(2).collect();
+    check(&run_cli_stdin(
+        &["--in", "sskr", "--out", "envelope"],
+        &two.join("\n"),
+    )?)?;
+
+    let mut args = vec!["--in", "hex", "--out", "multipart"];
+    args.extend_from_slice(&metadata_args);
+    args.extend_from_slice(&["--", "9d347f841a4e2ce6bc886e1aee74d824"]);
+    let parts = run_cli(&args)?;
+    check(&run_cli_stdin(
+        &["--in", "multipart", "--out", "envelope"],
+        &parts,
+    )?)?;
+
+    // The seed's own metadata has options of its own.
+    assert!(
+        run_cli(&["--out", "envelope", "--assertion", "'note'=A note"])
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_elision() -> Result<()> {
+    bc_envelope::register_tags();
+
+    let seed = "9d347f841a4e2ce6bc886e1aee74d824";
+    let signing_key = "ur:signing-private-key/lfaohdcxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxaxbyaomhte";
+    let verifying_key = "ur:signing-public-key/lfaohdcxwegadeswdettsaswwdwlaxetmhhkmdhsdthkdifthhiaytenensefgbbpsltemttihlumtvt";
+    let elided = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "envelope",
+        "--name",
+        "Alice",
+        "--note",
+        "Cold storage",
+        "--output-descriptor",
+        "wpkh([73c5da0a/84'/0'/0']xpub.../0/*)",
+        "--elide",
+        "seed,name,assertions",
+        "--signing-key",
+        signing_key,
+        "--",
+        seed,
+    ])?;
+    let envelope = Envelope::from_ur_string(&elided)?.subject().try_unwrap()?;
+    assert!(envelope.subject().is_elided());
+    assert_eq!(envelope.assertions().len(), 4);
+    let note: String =
+        envelope.extract_object_for_predicate(known_values::NOTE)?;
+    assert_eq!(note, "Cold storage");
+    assert!(
+        envelope
+            .extract_object_for_predicate::<String>(known_values::NAME)
+            .is_err()
+    );
+    // The revealed assertions are salted.
+    for assertion in envelope.assertions() {
+        assert!(
+            assertion.is_obscured()
+                || !assertion
+                    .assertions_with_predicate(known_values::SALT)
+                    .is_empty()
+        );
+    }
+
+    // An elided seed can't be decoded, only checked.
+    assert!(run_cli_stdin(&["--in", "envelope"], &elided).is_err());
+    let temp_dir = tempfile::tempdir()?;
+    let envelope_file = temp_dir.path().join("elided.ur");
+    let envelope_file = envelope_file.to_str().unwrap();
+    std::fs::write(envelope_file, format!("{}\n", elided))?;
+    run_cli_expect(
+        &[
+            "--in",
+            "hex",
+            "--envelope-check",
+            envelope_file,
+            "--verifying-key",
+            verifying_key,
+            "--",
+            seed,
+        ],
+        "The seed matches the envelope.",
+    )?;
+    assert!(
+        run_cli(&[
+            "--in",
+            "hex",
+            "--envelope-check",
+            envelope_file,
+            "--",
+            "9d347f841a4e2ce6bc886e1aee74d825",
+        ])
+        .is_err()
+    );
+
+    // With only metadata elided, the seed can still be decoded.
+    let partly_elided = run_cli(&[
+        "--in", "hex", "--out", "envelope", "--name", "Alice", "--elide",
+        "name", "--", seed,
+    ])?;
+    run_cli_expect_stdin(&["--in", "envelope"], seed, &partly_elided)?;
+
+    // Other outputs would reveal what is elided.
+    for out in [&["hex"][..], &["multipart"][..], &["envelope", "hex"][..]] {
+        let mut args = vec!["--in", "hex", "--elide", "seed"];
+        for key in out {
+            args.extend_from_slice(&["--out", key]);
+        }
+        args.extend_from_slice(&["--", seed]);
+        assert!(run_cli(&args).is_err());
+    }
+    assert!(
+        run_cli(&[
+            "--in",
+            "hex",
+            "--envelope-salt",
+            "--out",
+            "hex",
+            "--",
+            seed
+        ])
+        .is_err()
+    );
+
+    // Salted assertions keep their salt, and so their digests, when the
+    // envelope is read back and output again.
+    let salted = run_cli(&[
+        "--in",
+        "hex",
+        "--out",
+        "envelope",
+        "--name",
+        "Alice",
+        "--output-descriptor",
+        "wpkh([73c5da0a/84'/0'/0']xpub.../0/*)",
+        "--envelope-salt",
+        "--",
+        seed,
+    ])?;
+    let again =
+        run_cli_stdin(&["--in", "envelope", "--out", "envelope"], &salted)?;
+    assert_eq!(
+        Envelope::from_ur_string(&again)?.digest(),
+        Envelope::from_ur_string(&salted)?.digest()
+    );
+
+    Ok(())
+}